@@ -1,50 +1,364 @@
-use crate::config::ServerConfig;
-use crate::model::{MonitorEvent, MonitorPayload};
-use crate::ssh::SSHClient;
+use crate::config::{ReconnectConfig, ServerConfig};
+use crate::model::{ErrorKind, MonitorEvent, MonitorKind, MonitorPayload};
+use crate::monitor::{MonitorExecConfig, Monitorable};
+use crate::ssh::{HostKeyError, JumpPool};
+use crate::transport::{self, Connector, Transport};
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
-use tokio::sync::{broadcast::Receiver, mpsc::Sender};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, broadcast::Receiver, mpsc::Sender};
 use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// 带抖动的指数退避计时器。
+pub struct Backoff {
+    config: ReconnectConfig,
+    attempt: u32,
+}
+
+impl Backoff {
+    pub fn new(config: ReconnectConfig) -> Self {
+        Self { config, attempt: 0 }
+    }
+
+    /// 连接成功后调用，下次失败重新从 `initial_ms` 开始计算
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+
+    /// 不含抖动的第 `attempt` 次等待时间（毫秒）
+    pub fn base_delay_ms(&self) -> u64 {
+        let exp = self.config.multiplier.powi(self.attempt.min(64) as i32);
+        let delay = self.config.initial_ms as f64 * exp;
+        delay.min(self.config.max_ms as f64) as u64
+    }
+
+    /// 返回本次应等待的时间，并推进失败计数；加上抖动后仍不超过 `max_ms`
+    pub fn next_delay(&mut self) -> Duration {
+        let base = self.base_delay_ms() as f64;
+        self.attempt = self.attempt.saturating_add(1);
+        let jitter = self.config.jitter;
+        let factor = if jitter > 0.0 {
+            rand::rng().random_range(1.0 - jitter..=1.0 + jitter)
+        } else {
+            1.0
+        };
+        let delay = (base * factor).min(self.config.max_ms as f64);
+        Duration::from_millis(delay as u64)
+    }
+}
+
+/// 所有 agent 共享的并发上限：同时进行的连接与远程执行不超过 `global.concurrency`。
+#[derive(Clone)]
+pub struct Limiter {
+    semaphore: Arc<Semaphore>,
+    limit: usize,
+    waiting: Arc<AtomicUsize>,
+}
+
+/// 等待许可期间计入排队数；任务被取消时同样会减回去
+struct Waiting<'a>(&'a AtomicUsize);
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Limiter {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(limit)),
+            limit,
+            waiting: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub async fn acquire(&self) -> OwnedSemaphorePermit {
+        self.waiting.fetch_add(1, Ordering::Relaxed);
+        let _waiting = Waiting(&self.waiting);
+        self.semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("limiter semaphore is never closed")
+    }
+
+    /// 正在等待许可的任务数
+    pub fn queued(&self) -> usize {
+        self.waiting.load(Ordering::Relaxed)
+    }
+
+    /// 正在执行的连接或命令数
+    pub fn running(&self) -> usize {
+        self.limit - self.semaphore.available_permits()
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+/// 各监控项独立计时，同一时刻到期的监控项合并为一次执行。
+pub struct Schedule {
+    entries: Vec<(MonitorKind, Duration, Instant)>,
+}
+
+impl Schedule {
+    /// 所有监控项在 `start` 到期
+    pub fn new(server: &ServerConfig, start: Instant) -> Self {
+        let entries = server
+            .monitors
+            .iter()
+            .map(|kind| (kind.clone(), server.interval_of(kind), start))
+            .collect();
+        Self { entries }
+    }
+
+    /// 截至 `now` 已到期的监控项，按配置顺序排列
+    pub fn due(&self, now: Instant) -> Vec<MonitorKind> {
+        self.entries
+            .iter()
+            .filter(|(_, _, at)| *at <= now)
+            .map(|(kind, _, _)| kind.clone())
+            .collect()
+    }
+
+    /// 把刚执行过的监控项推迟一个周期；落后超过一个周期时不补跑
+    pub fn advance(&mut self, kinds: &[MonitorKind], now: Instant) {
+        for (kind, interval, at) in &mut self.entries {
+            if kinds.contains(kind) {
+                *at += *interval;
+                if *at <= now {
+                    *at = now + *interval;
+                }
+            }
+        }
+    }
+
+    /// 最近一个监控项的到期时间
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.entries.iter().map(|(_, _, at)| *at).min()
+    }
+}
 
 pub fn spawn_agent(
     server: ServerConfig,
     tx: Sender<MonitorEvent>,
     mut shutdown: Receiver<()>,
-    interval_ms: u64,
+    reconnect: ReconnectConfig,
+    pool: JumpPool,
+    limiter: Limiter,
+    start_delay: Duration,
 ) -> JoinHandle<()> {
     tokio::spawn(async move {
+        // 错开各 agent 的首次连接，避免同时握手
+        let mut schedule = Schedule::new(&server, Instant::now() + start_delay);
+        let mut agent = Agent {
+            exec_config: MonitorExecConfig::new(&server),
+            server,
+            connector: Connector::new(pool),
+            limiter,
+            tx,
+            client: None,
+            backoff: Backoff::new(reconnect),
+            previous: HashMap::new(),
+        };
         loop {
             tokio::select! {
                 _ = shutdown.recv() => {
-                    println!("Agent [{}] 收到退出信号", server.name);
+                    println!("Agent [{}] 收到退出信号", agent.server.name);
                     break;
                 }
                 _ = async {
-                    let client = SSHClient::connect_from_config(&server).await.expect("failed to connect to server");
-                    for kind in &server.monitors {
-                        let payload = MonitorPayload::from(kind);
-                        match client.exec(payload).await {
-                            Ok(payload) => {
-                                tx.send(MonitorEvent::Sample {
-                                    server: server.name.clone(),
-                                    kind: kind.clone(),
-                                    payload,
-                                    timestamp: chrono::Utc::now(),
-                                }).await.expect("failed to send monitor event");
-                            }
-                            Err(e) => {
-                                tx.send(MonitorEvent::Error {
-                                    server: server.name.clone(),
-                                    kind: Some(kind.clone()),
-                                    error: e.to_string(),
-                                    timestamp: chrono::Utc::now(),
-                                }).await.expect("failed to send monitor error event");
-                                continue;
-                            }
+                    let due = schedule.due(Instant::now());
+                    if !due.is_empty() {
+                        match agent.poll(&due).await {
+                            // 连接失败时到期的监控项保持到期，退避后重试
+                            Some(retry) => return tokio::time::sleep(retry).await,
+                            None => schedule.advance(&due, Instant::now()),
                         }
                     }
-                    tokio::time::sleep(Duration::from_millis(interval_ms)).await;
+                    if let Some(deadline) = schedule.next_deadline() {
+                        tokio::time::sleep_until(deadline).await;
+                    }
                 } => {}
             }
         }
+        if let Some(client) = agent.client {
+            let _ = client.disconnect().await;
+        }
     })
 }
+
+/// 单台服务器的采集状态
+struct Agent {
+    server: ServerConfig,
+    exec_config: MonitorExecConfig,
+    connector: Connector,
+    limiter: Limiter,
+    tx: Sender<MonitorEvent>,
+    client: Option<Box<dyn Transport>>,
+    backoff: Backoff,
+    /// 每个监控项上一次成功的采样，用于计算使用率与速率
+    previous: HashMap<MonitorKind, MonitorPayload>,
+}
+
+impl Agent {
+    /// 对到期的监控项执行一次采样；需要重连时返回退避等待时间。
+    ///
+    /// 连接只在不存在或传输层出错后才重建；重连失败按退避策略延长等待。
+    async fn poll(&mut self, due: &[MonitorKind]) -> Option<Duration> {
+        if self.client.is_none() {
+            let connected = {
+                let _permit = self.limiter.acquire().await;
+                match self.connector.connect(&self.server).await {
+                    // 每次连接都重新识别远端系统，决定各监控项的命令
+                    Ok(conn) => conn.probe().await.map(|platform| (conn, platform)),
+                    Err(e) => Err(e),
+                }
+            };
+            match connected {
+                Ok((conn, (os, caps))) => {
+                    self.backoff.reset();
+                    if self.exec_config.os != os {
+                        // 不同系统的计数器不可比较
+                        self.previous.clear();
+                        self.exec_config.os = os;
+                    }
+                    self.exec_config.caps = caps;
+                    self.client = Some(conn);
+                    self.report_unsupported().await;
+                }
+                Err(e) => {
+                    let delay = self.backoff.next_delay();
+                    let category = match e.downcast_ref::<HostKeyError>() {
+                        Some(err) if err.mismatch => ErrorKind::HostKeyMismatch,
+                        Some(_) => ErrorKind::HostKeyUnknown,
+                        None => ErrorKind::Connect,
+                    };
+                    self.send_error(
+                        None,
+                        category,
+                        format!("connect failed: {e}, retry in {}ms", delay.as_millis()),
+                    )
+                    .await;
+                    return Some(delay);
+                }
+            }
+        }
+        let conn = self.client.as_ref()?;
+
+        // 到期的监控项合并为一次执行；单项的退出码或解析错误不影响其他项
+        let os = self.exec_config.os;
+        let due: Vec<MonitorKind> = due
+            .iter()
+            .filter(|kind| MonitorPayload::from(*kind).supported(os))
+            .cloned()
+            .collect();
+        if due.is_empty() {
+            return None;
+        }
+        let payloads = due.iter().map(MonitorPayload::from).collect();
+        let results = {
+            let _permit = self.limiter.acquire().await;
+            conn.exec_all(payloads, &self.exec_config).await
+        };
+        let results = match results {
+            Ok(results) => results,
+            Err(e) => {
+                let broken = transport::is_transport_error(&e) || conn.is_closed();
+                self.send_error(None, ErrorKind::Monitor, e.to_string())
+                    .await;
+                if broken {
+                    // 丢弃失效连接，下一轮重新建立
+                    self.client = None;
+                    return Some(self.backoff.next_delay());
+                }
+                return None;
+            }
+        };
+        for (kind, result) in due.iter().zip(results) {
+            // 插件在本机解析，不占用并发许可
+            let result = match result {
+                Ok(mut payload) => payload.finish(&self.exec_config).await.map(|()| payload),
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(mut payload) => {
+                    let alerts = match self.previous.get(kind) {
+                        Some(prev) => {
+                            payload.apply_previous(prev);
+                            payload.alerts(prev)
+                        }
+                        None => Vec::new(),
+                    };
+                    self.previous.insert(kind.clone(), payload.clone());
+                    let _ = self
+                        .tx
+                        .send(MonitorEvent::Sample {
+                            server: self.server.name.clone(),
+                            kind: kind.clone(),
+                            payload: Box::new(payload),
+                            timestamp: chrono::Utc::now(),
+                        })
+                        .await;
+                    for alert in alerts {
+                        let _ = self
+                            .tx
+                            .send(MonitorEvent::Alert {
+                                server: self.server.name.clone(),
+                                kind: kind.clone(),
+                                alert,
+                                timestamp: chrono::Utc::now(),
+                            })
+                            .await;
+                    }
+                }
+                Err(e) => {
+                    self.send_error(Some(kind.clone()), ErrorKind::Monitor, e.to_string())
+                        .await;
+                }
+            }
+        }
+        None
+    }
+
+    /// 远端系统不支持的监控项不会执行，连接后只报告一次。
+    ///
+    /// 错误挂在第一个不支持的监控项上，这样其他监控项的样本不会把它清除。
+    async fn report_unsupported(&self) {
+        let os = self.exec_config.os;
+        let unsupported: Vec<&MonitorKind> = self
+            .server
+            .monitors
+            .iter()
+            .filter(|kind| !MonitorPayload::from(*kind).supported(os))
+            .collect();
+        let Some(first) = unsupported.first() else {
+            return;
+        };
+        let names: Vec<String> = unsupported.iter().map(|k| format!("{k:?}")).collect();
+        self.send_error(
+            Some((*first).clone()),
+            ErrorKind::Monitor,
+            format!("{} not supported on {os:?}, skipped", names.join(", ")),
+        )
+        .await;
+    }
+
+    async fn send_error(&self, kind: Option<MonitorKind>, category: ErrorKind, error: String) {
+        let _ = self
+            .tx
+            .send(MonitorEvent::Error {
+                server: self.server.name.clone(),
+                kind,
+                category,
+                error,
+                timestamp: chrono::Utc::now(),
+            })
+            .await;
+    }
+}
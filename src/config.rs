@@ -1,29 +1,180 @@
 use crate::model::MonitorKind;
+use crate::monitor::{CustomMonitor, MonitorOpts, Plugin, PluginConfig};
+use crate::ssh_config::{self, SshConfig};
 use crate::ui::DisplayKind;
 use anyhow::Result;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// 全局配置根结构体，从 config.toml 反序列化。
 #[derive(Debug, Deserialize)]
 pub struct Config {
     pub global: GlobalConfig,
     pub servers: Vec<ServerConfig>,
+    /// 自定义监控项，服务器在 `monitors` 中按名字引用
+    #[serde(default)]
+    pub custom_monitors: Vec<CustomMonitor>,
+    /// 外部监控插件，服务器在 `monitors` 中按插件声明的名字引用
+    #[serde(default)]
+    pub plugins: Vec<PluginConfig>,
 }
 
 impl Config {
     /// 从指定路径加载配置文件并反序列化为 Config 结构体。
-    pub fn load_config<P: AsRef<Path>>(path: P) -> Result<Self> {
+    pub async fn load_config<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content = fs::read_to_string(path)?;
-        let config: Config = toml::from_str(&content)?;
+        let mut table: toml::Table = toml::from_str(&content)?;
+        Self::apply_ssh_config(&mut table)?;
+        let mut config: Config = table.try_into()?;
+        config.resolve_jumps()?;
+        config.resolve_custom_monitors();
+        config.resolve_plugins().await?;
+        for server in &mut config.servers {
+            server.host_key_check.expand_home();
+            for hop in &mut server.jump_chain {
+                hop.host_key_check.expand_home();
+            }
+        }
         config.validate()?;
         Ok(config)
     }
 
+    /// 若有服务器设置了 `ssh_alias`，读取 `global.ssh_config`（默认 ~/.ssh/config）补全其连接参数
+    fn apply_ssh_config(table: &mut toml::Table) -> Result<()> {
+        let uses_alias = table
+            .get("servers")
+            .and_then(|s| s.as_array())
+            .is_some_and(|servers| servers.iter().any(|s| s.get("ssh_alias").is_some()));
+        if !uses_alias {
+            return Ok(());
+        }
+        // 服务器表要先补全才能反序列化，global 可以先单独解析
+        let global: GlobalConfig = table
+            .get("global")
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("missing [global] section"))?
+            .try_into()?;
+        let ssh = SshConfig::load(global.ssh_config_path())?;
+        ssh_config::apply_to_servers(table, &ssh)
+    }
+
+    /// 把每台服务器的 `jump` 列表展开为完整的跳板链。
+    ///
+    /// 引用其它服务器名时，会连同那台服务器自己的跳板链一起展开。
+    pub fn resolve_jumps(&mut self) -> Result<()> {
+        let mut chains = Vec::with_capacity(self.servers.len());
+        for server in &self.servers {
+            chains.push(self.jump_chain_of(server, &mut vec![server.name.clone()])?);
+        }
+        for (server, chain) in self.servers.iter_mut().zip(chains) {
+            server.jump_chain = chain;
+        }
+        Ok(())
+    }
+
+    /// 把每台服务器引用的 `[[custom_monitors]]` 定义复制到它的 `monitor_opts` 中
+    pub fn resolve_custom_monitors(&mut self) {
+        for server in &mut self.servers {
+            server.monitor_opts.custom = self
+                .custom_monitors
+                .iter()
+                .filter(|m| {
+                    server
+                        .monitors
+                        .contains(&MonitorKind::Custom(m.name.clone()))
+                })
+                .cloned()
+                .collect();
+        }
+    }
+
+    /// 执行各插件的 `describe`，把服务器引用的插件复制到它的 `monitor_opts` 中
+    pub async fn resolve_plugins(&mut self) -> Result<()> {
+        let mut plugins: Vec<Plugin> = Vec::with_capacity(self.plugins.len());
+        for config in &mut self.plugins {
+            config.path = expand_home(&config.path);
+            let plugin = Plugin::load(config).await?;
+            if plugins.iter().any(|p| p.name == plugin.name) {
+                anyhow::bail!("Plugin {} is loaded more than once", plugin.name);
+            }
+            if let Some(interval) = plugin.interval_ms
+                && interval < MIN_POLL_INTERVAL_MS
+            {
+                anyhow::bail!(
+                    "Plugin {} interval_ms must be at least {MIN_POLL_INTERVAL_MS}",
+                    plugin.name
+                );
+            }
+            plugins.push(plugin);
+        }
+        for server in &mut self.servers {
+            server.monitor_opts.plugins = plugins
+                .iter()
+                .filter(|p| {
+                    server
+                        .monitors
+                        .contains(&MonitorKind::Plugin(p.name.clone()))
+                })
+                .cloned()
+                .collect();
+        }
+        Ok(())
+    }
+
+    fn jump_chain_of(
+        &self,
+        server: &ServerConfig,
+        visiting: &mut Vec<String>,
+    ) -> Result<Vec<HopConfig>> {
+        let mut chain = Vec::new();
+        for jump in &server.jump {
+            match jump {
+                JumpHost::Inline(hop) => chain.push(hop.clone()),
+                JumpHost::Named(name) => {
+                    if visiting.contains(name) {
+                        anyhow::bail!(
+                            "Jump chain of server {} loops back to {}",
+                            server.name,
+                            name
+                        );
+                    }
+                    if let Some(bastion) = self.servers.iter().find(|s| &s.name == name) {
+                        visiting.push(name.clone());
+                        chain.extend(self.jump_chain_of(bastion, visiting)?);
+                        visiting.pop();
+                        chain.push(bastion.hop());
+                    } else {
+                        chain.push(HopConfig::parse_spec(name, server)?);
+                    }
+                }
+            }
+        }
+        Ok(chain)
+    }
+
     /// 检查合法性
     pub fn validate(&self) -> Result<()> {
         self.global.validate()?;
+        for (idx, monitor) in self.custom_monitors.iter().enumerate() {
+            monitor.validate()?;
+            if self.custom_monitors[..idx]
+                .iter()
+                .any(|m| m.name == monitor.name)
+            {
+                anyhow::bail!("Custom monitor {} is defined more than once", monitor.name);
+            }
+            if let Some(interval) = monitor.interval_ms
+                && interval < MIN_POLL_INTERVAL_MS
+            {
+                anyhow::bail!(
+                    "Custom monitor {} interval_ms must be at least {MIN_POLL_INTERVAL_MS}",
+                    monitor.name
+                );
+            }
+        }
         for server in &self.servers {
             server.validate()?;
         }
@@ -39,12 +190,79 @@ pub struct GlobalConfig {
     /// 显示模式
     #[serde(default = "Default::default")]
     pub display: DisplayKind,
+    /// 同时进行的连接与远程执行的上限
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    /// 相邻两台服务器首次连接之间的间隔，单位毫秒
+    #[serde(default = "default_startup_stagger")]
+    pub startup_stagger_ms: u64,
+    /// SSH 断线重连的退避策略
+    #[serde(default)]
+    pub reconnect: ReconnectConfig,
+    /// 解析 `ssh_alias` 时读取的 OpenSSH 配置文件，默认 ~/.ssh/config
+    pub ssh_config: Option<PathBuf>,
 }
 impl GlobalConfig {
+    /// 展开 `~` 后的 OpenSSH 配置文件路径
+    pub fn ssh_config_path(&self) -> PathBuf {
+        expand_home(
+            self.ssh_config
+                .as_deref()
+                .unwrap_or(Path::new("~/.ssh/config")),
+        )
+    }
+
     pub fn validate(&self) -> Result<()> {
         if self.refresh < 200 {
             anyhow::bail!("Global refresh interval must be greater than 200");
         }
+        if self.concurrency == 0 {
+            anyhow::bail!("Global concurrency must be at least 1");
+        }
+        self.reconnect.validate()?;
+        Ok(())
+    }
+}
+
+/// 指数退避参数：第 n 次失败后等待 `initial_ms * multiplier^n`，
+/// 上限为 `max_ms`，并在 ±`jitter` 比例内随机抖动。
+#[derive(Debug, Deserialize, Clone)]
+pub struct ReconnectConfig {
+    #[serde(default = "default_backoff_initial")]
+    pub initial_ms: u64,
+    #[serde(default = "default_backoff_max")]
+    pub max_ms: u64,
+    #[serde(default = "default_backoff_multiplier")]
+    pub multiplier: f64,
+    #[serde(default = "default_backoff_jitter")]
+    pub jitter: f64,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            initial_ms: default_backoff_initial(),
+            max_ms: default_backoff_max(),
+            multiplier: default_backoff_multiplier(),
+            jitter: default_backoff_jitter(),
+        }
+    }
+}
+
+impl ReconnectConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.initial_ms == 0 {
+            anyhow::bail!("Reconnect initial_ms must be greater than 0");
+        }
+        if self.max_ms < self.initial_ms {
+            anyhow::bail!("Reconnect max_ms must not be less than initial_ms");
+        }
+        if self.multiplier < 1.0 {
+            anyhow::bail!("Reconnect multiplier must be at least 1.0");
+        }
+        if !(0.0..=1.0).contains(&self.jitter) {
+            anyhow::bail!("Reconnect jitter must be between 0.0 and 1.0");
+        }
         Ok(())
     }
 }
@@ -52,21 +270,326 @@ impl GlobalConfig {
 #[derive(Debug, Deserialize, Clone)]
 pub struct ServerConfig {
     pub name: String,
+    /// ~/.ssh/config 中的 Host 别名，用于补全未显式填写的连接参数
+    pub ssh_alias: Option<String>,
+    /// 命令执行方式，`local` 时不需要 host/user 等 SSH 参数
+    #[serde(default)]
+    pub transport: TransportKind,
+    /// `replay` 使用的夹具目录
+    pub fixtures: Option<PathBuf>,
+    #[serde(default)]
     pub host: String,
     #[serde(default = "default_port")]
     pub port: u16,
+    #[serde(default)]
     pub user: String,
     pub password: Option<String>,
     pub privkey_path: Option<PathBuf>,
     pub passphrase: Option<String>,
+    /// 认证方式，缺省时按 privkey_path > password > ssh-agent 自动选择
+    pub auth: Option<AuthKind>,
     pub monitors: Vec<MonitorKind>,
+    /// 采样间隔，单位毫秒，与 UI 刷新间隔无关
+    #[serde(default = "default_poll_interval")]
+    pub poll_interval_ms: u64,
+    /// 单个监控项的采样间隔，覆盖 `poll_interval_ms`，如 `{ Disk = 60000 }`
+    #[serde(default)]
+    pub monitor_interval_ms: HashMap<MonitorKind, u64>,
+    /// 各监控项的参数，如 `[servers.monitor_opts.disk] exclude_fs = [...]`
+    #[serde(default)]
+    pub monitor_opts: MonitorOpts,
+    /// 主机密钥校验方式，默认使用 ~/.ssh/known_hosts
+    #[serde(default)]
+    pub host_key_check: HostKeyCheck,
+    /// 依次经过的跳板机，按连接顺序排列
+    #[serde(default)]
+    pub jump: Vec<JumpHost>,
+    /// 由 `jump` 展开得到的完整跳板链，加载配置时填充
+    #[serde(skip)]
+    pub jump_chain: Vec<HopConfig>,
+}
+
+/// `jump` 中的一项：引用另一台服务器的 name、`[user@]host[:port]`，或内联连接参数。
+///
+/// ```toml
+/// jump = ["bastion"]
+/// jump = ["ops@10.0.0.1:2222", { host = "10.1.0.1", user = "root", auth = "agent" }]
+/// ```
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum JumpHost {
+    Named(String),
+    Inline(HopConfig),
+}
+
+/// 建立单跳 SSH 连接所需的参数
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct HopConfig {
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    pub user: String,
+    pub password: Option<String>,
+    pub privkey_path: Option<PathBuf>,
+    pub passphrase: Option<String>,
+    pub auth: Option<AuthKind>,
+    #[serde(default)]
+    pub host_key_check: HostKeyCheck,
+}
+
+/// 把跳板机的 `host[:port]` 拆成主机与端口，`[user@]` 前缀须已去掉。
+///
+/// IPv6 地址带端口时写作 `[2001:db8::1]:2222`；不带方括号的多个冒号整体视为主机。
+pub(crate) fn split_host_port(spec: &str) -> Result<(&str, Option<u16>)> {
+    let (host, port) = if let Some(bracketed) = spec.strip_prefix('[') {
+        let (host, after) = bracketed
+            .split_once(']')
+            .ok_or_else(|| anyhow::anyhow!("Unclosed '[' in jump host {spec:?}"))?;
+        match after {
+            "" => (host, None),
+            _ => match after.strip_prefix(':') {
+                Some(port) => (host, Some(port)),
+                None => anyhow::bail!("Unexpected {after:?} after ']' in jump host {spec:?}"),
+            },
+        }
+    } else {
+        match spec.split_once(':') {
+            Some((host, port)) if !port.contains(':') => (host, Some(port)),
+            _ => (spec, None),
+        }
+    };
+    let port = match port {
+        Some(port) => Some(
+            port.parse()
+                .map_err(|_| anyhow::anyhow!("Invalid port in jump host {spec:?}"))?,
+        ),
+        None => None,
+    };
+    Ok((host, port))
+}
+
+impl HopConfig {
+    /// 不经跳板、尚未指定认证参数的一跳
+    pub fn direct(host: &str, port: u16, user: &str, host_key_check: HostKeyCheck) -> Self {
+        Self {
+            host: host.to_string(),
+            port,
+            user: user.to_string(),
+            password: None,
+            privkey_path: None,
+            passphrase: None,
+            auth: None,
+            host_key_check,
+        }
+    }
+
+    /// 解析 `[user@]host[:port]`，认证与主机密钥设置沿用目标服务器
+    fn parse_spec(spec: &str, server: &ServerConfig) -> Result<Self> {
+        let (user, rest) = match spec.split_once('@') {
+            Some((user, rest)) => (user.to_string(), rest),
+            None => (server.user.clone(), spec),
+        };
+        let (host, port) = split_host_port(rest)?;
+        let port = port.unwrap_or_else(default_port);
+        let host = host.to_string();
+        Ok(Self {
+            host,
+            port,
+            user,
+            ..server.hop()
+        })
+    }
+
+    /// 形如 `user@host:port` 的标识，用于日志和连接复用
+    pub fn label(&self) -> String {
+        format!("{}@{}:{}", self.user, self.host, self.port)
+    }
+
+    /// 实际使用的认证方式
+    pub fn auth_kind(&self) -> AuthKind {
+        select_auth(self.auth, &self.privkey_path, &self.password)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.host.trim().is_empty() {
+            anyhow::bail!("Jump host cannot be empty");
+        }
+        if self.port == 0 {
+            anyhow::bail!("Jump host {} port must be between 1 and 65535", self.host);
+        }
+        if self.user.trim().is_empty() {
+            anyhow::bail!("Jump host {} user cannot be empty", self.host);
+        }
+        match self.auth {
+            Some(AuthKind::Key) if self.privkey_path.is_none() => {
+                anyhow::bail!(
+                    "Jump host {} uses key auth but has no privkey_path",
+                    self.host
+                );
+            }
+            Some(AuthKind::Password) if self.password.is_none() => {
+                anyhow::bail!(
+                    "Jump host {} uses password auth but has no password",
+                    self.host
+                );
+            }
+            _ => {}
+        }
+        self.host_key_check.validate()
+    }
+}
+
+/// 监控命令的执行方式
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TransportKind {
+    #[default]
+    Ssh,
+    /// 在运行 stalking 的本机上直接执行
+    Local,
+    /// 从 `fixtures` 目录回放预先录制的输出，用于测试与演示
+    Replay,
+}
+
+/// SSH 认证方式
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthKind {
+    Password,
+    Key,
+    /// 通过 `SSH_AUTH_SOCK` 使用 ssh-agent 中的身份
+    Agent,
+}
+
+/// 服务器主机密钥的校验方式。
+///
+/// ```toml
+/// host_key_check = "KnownHosts"                              # ~/.ssh/known_hosts
+/// host_key_check = { KnownHostsFile = "/etc/ssh/ssh_known_hosts" }
+/// host_key_check = { Fingerprint = "SHA256:..." }           # ssh-keygen -lf 的输出
+/// host_key_check = "Insecure"                               # 不校验，需显式开启
+/// ```
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+pub enum HostKeyCheck {
+    #[default]
+    KnownHosts,
+    KnownHostsFile(PathBuf),
+    Fingerprint(String),
+    Insecure,
+}
+
+impl HostKeyCheck {
+    /// 展开 known_hosts 路径中的 `~`
+    pub fn expand_home(&mut self) {
+        if let HostKeyCheck::KnownHostsFile(path) = self {
+            *path = expand_home(path);
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            HostKeyCheck::Fingerprint(fp) => {
+                let fp = fp.trim();
+                let hash = fp.strip_prefix("SHA256:").unwrap_or(fp);
+                if hash.is_empty() || (fp.contains(':') && !fp.starts_with("SHA256:")) {
+                    anyhow::bail!("Host key fingerprint must be a SHA256 fingerprint, got {fp:?}");
+                }
+            }
+            HostKeyCheck::KnownHostsFile(path) if path.as_os_str().is_empty() => {
+                anyhow::bail!("known_hosts file path cannot be empty");
+            }
+            _ => {}
+        }
+        Ok(())
+    }
 }
 
 impl ServerConfig {
+    /// 监控项 `kind` 的实际采样间隔
+    pub fn interval_of(&self, kind: &MonitorKind) -> Duration {
+        let custom = match kind {
+            MonitorKind::Custom(name) => self.monitor_opts.custom(name).and_then(|m| m.interval_ms),
+            MonitorKind::Plugin(name) => self.monitor_opts.plugin(name).and_then(|p| p.interval_ms),
+            _ => None,
+        };
+        let ms = self
+            .monitor_interval_ms
+            .get(kind)
+            .copied()
+            .or(custom)
+            .unwrap_or(self.poll_interval_ms);
+        Duration::from_millis(ms)
+    }
+
     pub fn validate(&self) -> Result<()> {
         if self.name.trim().is_empty() {
             anyhow::bail!("Server name cannot be empty");
         }
+        if self.monitors.is_empty() {
+            anyhow::bail!(
+                "At least one monitor must be specified for server {}",
+                self.name
+            );
+        }
+        if self.poll_interval_ms < MIN_POLL_INTERVAL_MS {
+            anyhow::bail!(
+                "Server {} poll_interval_ms must be at least {MIN_POLL_INTERVAL_MS}",
+                self.name
+            );
+        }
+        for (kind, interval) in &self.monitor_interval_ms {
+            if !self.monitors.contains(kind) {
+                anyhow::bail!(
+                    "Server {} sets an interval for {kind:?}, which is not in monitors",
+                    self.name
+                );
+            }
+            if *interval < MIN_POLL_INTERVAL_MS {
+                anyhow::bail!(
+                    "Server {} interval for {kind:?} must be at least {MIN_POLL_INTERVAL_MS}",
+                    self.name
+                );
+            }
+        }
+        for kind in &self.monitors {
+            match kind {
+                MonitorKind::Custom(name) if self.monitor_opts.custom(name).is_none() => {
+                    anyhow::bail!(
+                        "Server {} uses custom monitor {name}, which is not defined in [[custom_monitors]]",
+                        self.name
+                    );
+                }
+                MonitorKind::Plugin(name) if self.monitor_opts.plugin(name).is_none() => {
+                    anyhow::bail!(
+                        "Server {} uses plugin {name}, which no [[plugins]] entry provides",
+                        self.name
+                    );
+                }
+                MonitorKind::Process if self.monitor_opts.process.watch.is_empty() => {
+                    anyhow::bail!(
+                        "Server {} uses the Process monitor but [servers.monitor_opts.process] watches nothing",
+                        self.name
+                    );
+                }
+                _ => {}
+            }
+        }
+        self.monitor_opts
+            .validate()
+            .map_err(|e| anyhow::anyhow!("Server {}: {e}", self.name))?;
+        match self.transport {
+            TransportKind::Ssh => {}
+            TransportKind::Local => return Ok(()),
+            TransportKind::Replay => {
+                if self.fixtures.is_none() {
+                    anyhow::bail!(
+                        "Server {} uses replay transport but has no fixtures",
+                        self.name
+                    );
+                }
+                return Ok(());
+            }
+        }
         if self.host.trim().is_empty() {
             anyhow::bail!("Server host cannot be empty");
         }
@@ -76,14 +599,79 @@ impl ServerConfig {
         if self.user.trim().is_empty() {
             anyhow::bail!("Server user cannot be empty");
         }
-        if self.monitors.is_empty() {
-            anyhow::bail!(
-                "At least one monitor must be specified for server {}",
-                self.name
-            );
+        match self.auth {
+            Some(AuthKind::Key) if self.privkey_path.is_none() => {
+                anyhow::bail!("Server {} uses key auth but has no privkey_path", self.name);
+            }
+            Some(AuthKind::Password) if self.password.is_none() => {
+                anyhow::bail!(
+                    "Server {} uses password auth but has no password",
+                    self.name
+                );
+            }
+            _ => {}
+        }
+        self.host_key_check.validate()?;
+        for hop in &self.jump_chain {
+            hop.validate()?;
         }
         Ok(())
     }
+
+    /// 实际使用的认证方式
+    pub fn auth_kind(&self) -> AuthKind {
+        select_auth(self.auth, &self.privkey_path, &self.password)
+    }
+
+    /// 连接这台服务器本身（最后一跳）的参数
+    pub fn hop(&self) -> HopConfig {
+        HopConfig {
+            host: self.host.clone(),
+            port: self.port,
+            user: self.user.clone(),
+            password: self.password.clone(),
+            privkey_path: self.privkey_path.clone(),
+            passphrase: self.passphrase.clone(),
+            auth: self.auth,
+            host_key_check: self.host_key_check.clone(),
+        }
+    }
+}
+
+/// 未显式指定时按 privkey_path > password > ssh-agent 选择认证方式
+fn select_auth(
+    auth: Option<AuthKind>,
+    privkey_path: &Option<PathBuf>,
+    password: &Option<String>,
+) -> AuthKind {
+    match auth {
+        Some(kind) => kind,
+        None if privkey_path.is_some() => AuthKind::Key,
+        None if password.is_some() => AuthKind::Password,
+        None => AuthKind::Agent,
+    }
+}
+
+/// 展开路径开头的 `~`
+pub fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn default_concurrency() -> usize {
+    8
+}
+
+fn default_startup_stagger() -> u64 {
+    50
+}
+
+const MIN_POLL_INTERVAL_MS: u64 = 100;
+
+fn default_poll_interval() -> u64 {
+    1000
 }
 
 fn default_refresh() -> u64 {
@@ -92,3 +680,15 @@ fn default_refresh() -> u64 {
 fn default_port() -> u16 {
     22
 }
+fn default_backoff_initial() -> u64 {
+    1000
+}
+fn default_backoff_max() -> u64 {
+    60_000
+}
+fn default_backoff_multiplier() -> f64 {
+    2.0
+}
+fn default_backoff_jitter() -> f64 {
+    0.2
+}
@@ -3,6 +3,8 @@ mod config;
 mod model;
 mod monitor;
 pub mod ssh;
+mod ssh_config;
+mod transport;
 #[cfg(test)]
 mod tests;
 mod ui;
@@ -27,20 +29,32 @@ struct Cli {
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
-    let config = Config::load_config(&cli.config_path)?;
+    let config = Config::load_config(&cli.config_path).await?;
     let (tx, rx) = tokio::sync::mpsc::channel(100);
     let (shutdown_tx, _) = tokio::sync::broadcast::channel(1);
+    let limiter = agent::Limiter::new(config.global.concurrency);
+    let kinds = ui::page_kinds(&config.servers);
     let ui_handle = match config.global.display {
         ui::DisplayKind::Tui => ui::spawn_tui(rx, config.global.refresh, shutdown_tx.clone(), config.servers.iter().map(
-            |s| s.name.clone()).collect::<Vec<_>>()),
+            |s| s.name.clone()).collect::<Vec<_>>(), kinds.clone(), limiter.clone()),
         ui::DisplayKind::Plain => ui::spawn_plain(rx, config.global.refresh, shutdown_tx.clone(), config.servers.iter().map(
-            |s| s.name.clone()).collect::<Vec<_>>()),
+            |s| s.name.clone()).collect::<Vec<_>>(), kinds.clone(), limiter.clone()),
     };
 
     let mut agent_handles = Vec::new();
-    for server in config.servers {
+    let jump_pool = ssh::JumpPool::default();
+    for (idx, server) in config.servers.into_iter().enumerate() {
         let shutdown_rx = shutdown_tx.subscribe();
-        let handle = agent::spawn_agent(server, tx.clone(), shutdown_rx, config.global.refresh);
+        let start_delay = std::time::Duration::from_millis(config.global.startup_stagger_ms * idx as u64);
+        let handle = agent::spawn_agent(
+            server,
+            tx.clone(),
+            shutdown_rx,
+            config.global.reconnect.clone(),
+            jump_pool.clone(),
+            limiter.clone(),
+            start_delay,
+        );
         agent_handles.push(handle);
     }
 
@@ -1,4 +1,10 @@
-use crate::monitor::{CpuInfo, DiskInfo, MemInfo, Monitorable, NetInfo};
+use crate::monitor::load::format_duration;
+use crate::monitor::sockets::ListenSocket;
+use crate::monitor::{
+    CpuInfo, CustomInfo, DiskInfo, DiskIoInfo, LoadInfo, MemInfo, MonitorExecConfig, Monitorable,
+    NetInfo, PluginInfo, PressureInfo, ProcessWatchInfo, RemoteOs, SensorsInfo, SocketsInfo,
+    SystemdInfo,
+};
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
@@ -8,11 +14,50 @@ pub enum MonitorKind {
     Cpu,
     Disk,
     Net,
+    /// 块设备的 I/O 吞吐与延迟
+    DiskIo,
+    /// 平均负载、开机时长与重启检测
+    Load,
+    /// PSI、换页速率与 OOM kill
+    Pressure,
+    /// `monitor_opts.process.watch` 中的进程
+    Process,
+    /// systemd unit 的状态
+    Systemd,
+    /// hwmon 与 thermal zone 的温度、风扇转速
+    Sensors,
+    /// TCP 状态统计、重传与监听端口
+    Sockets,
+    /// `[[custom_monitors]]` 中定义的监控项，写作 `{ Custom = "<name>" }`
+    Custom(String),
+    /// `[[plugins]]` 加载的外部插件，写作 `{ Plugin = "<name>" }`
+    Plugin(String),
 }
 
 impl MonitorKind {
+    /// 页面标题
+    pub fn label(&self) -> String {
+        match self {
+            MonitorKind::Mem => "MEM".to_string(),
+            MonitorKind::Cpu => "CPU".to_string(),
+            MonitorKind::Disk => "DISK".to_string(),
+            MonitorKind::Net => "NET".to_string(),
+            MonitorKind::DiskIo => "DISKIO".to_string(),
+            MonitorKind::Load => "LOAD".to_string(),
+            MonitorKind::Pressure => "PRESSURE".to_string(),
+            MonitorKind::Process => "PROC".to_string(),
+            MonitorKind::Systemd => "SYSTEMD".to_string(),
+            MonitorKind::Sensors => "SENSORS".to_string(),
+            MonitorKind::Sockets => "SOCKETS".to_string(),
+            MonitorKind::Custom(name) | MonitorKind::Plugin(name) => name.clone(),
+        }
+    }
+
     pub fn variants() -> Vec<&'static str> {
-        vec!["mem", "cpu", "disk", "net"]
+        vec![
+            "mem", "cpu", "disk", "net", "diskio", "load", "pressure", "process", "systemd",
+            "sensors", "sockets",
+        ]
     }
 }
 
@@ -25,17 +70,34 @@ impl TryFrom<&str> for MonitorKind {
             "cpu" => Ok(MonitorKind::Cpu),
             "disk" => Ok(MonitorKind::Disk),
             "net" => Ok(MonitorKind::Net),
+            "diskio" => Ok(MonitorKind::DiskIo),
+            "load" => Ok(MonitorKind::Load),
+            "pressure" => Ok(MonitorKind::Pressure),
+            "process" => Ok(MonitorKind::Process),
+            "systemd" => Ok(MonitorKind::Systemd),
+            "sensors" => Ok(MonitorKind::Sensors),
+            "sockets" => Ok(MonitorKind::Sockets),
             _ => Err(format!("unknown monitor kind: {}", value)),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Default)]
 pub enum MonitorPayload {
     Mem(MemInfo),
     Cpu(CpuInfo),
     Disk(DiskInfo),
+    DiskIo(DiskIoInfo),
     Net(NetInfo),
+    Load(LoadInfo),
+    Pressure(PressureInfo),
+    Process(ProcessWatchInfo),
+    Systemd(SystemdInfo),
+    Sensors(SensorsInfo),
+    Sockets(SocketsInfo),
+    Custom(CustomInfo),
+    Plugin(PluginInfo),
+    #[default]
     None,
 }
 
@@ -46,60 +108,218 @@ impl From<&MonitorKind> for MonitorPayload {
             MonitorKind::Cpu => MonitorPayload::Cpu(CpuInfo::default()),
             MonitorKind::Disk => MonitorPayload::Disk(DiskInfo::default()),
             MonitorKind::Net => MonitorPayload::Net(NetInfo::default()),
+            MonitorKind::DiskIo => MonitorPayload::DiskIo(DiskIoInfo::default()),
+            MonitorKind::Load => MonitorPayload::Load(LoadInfo::default()),
+            MonitorKind::Pressure => MonitorPayload::Pressure(PressureInfo::default()),
+            MonitorKind::Process => MonitorPayload::Process(ProcessWatchInfo::default()),
+            MonitorKind::Systemd => MonitorPayload::Systemd(SystemdInfo::default()),
+            MonitorKind::Sensors => MonitorPayload::Sensors(SensorsInfo::default()),
+            MonitorKind::Sockets => MonitorPayload::Sockets(SocketsInfo::default()),
+            MonitorKind::Custom(name) => MonitorPayload::Custom(CustomInfo::new(name)),
+            MonitorKind::Plugin(name) => MonitorPayload::Plugin(PluginInfo::new(name)),
         }
     }
 }
 
-impl Default for MonitorPayload {
-    fn default() -> Self {
-        MonitorPayload::None
+impl MonitorPayload {
+    /// 完成需要在本机执行的解析步骤（目前只有插件）；在释放并发许可后调用
+    pub async fn finish(&mut self, config: &MonitorExecConfig) -> anyhow::Result<()> {
+        match self {
+            MonitorPayload::Plugin(info) => info.run_plugin(config).await,
+            _ => Ok(()),
+        }
     }
 }
 
 impl Monitorable for MonitorPayload {
-    fn exec_cmd(&self) -> &'static str {
+    fn supported(&self, os: RemoteOs) -> bool {
         match self {
-            MonitorPayload::Mem(info) => info.exec_cmd(),
-            MonitorPayload::Cpu(info) => info.exec_cmd(),
-            MonitorPayload::Disk(info) => info.exec_cmd(),
-            MonitorPayload::Net(info) => info.exec_cmd(),
-            MonitorPayload::None => "",
+            MonitorPayload::Mem(info) => info.supported(os),
+            MonitorPayload::Cpu(info) => info.supported(os),
+            MonitorPayload::Disk(info) => info.supported(os),
+            MonitorPayload::Net(info) => info.supported(os),
+            MonitorPayload::DiskIo(info) => info.supported(os),
+            MonitorPayload::Load(info) => info.supported(os),
+            MonitorPayload::Pressure(info) => info.supported(os),
+            MonitorPayload::Process(info) => info.supported(os),
+            MonitorPayload::Systemd(info) => info.supported(os),
+            MonitorPayload::Sensors(info) => info.supported(os),
+            MonitorPayload::Sockets(info) => info.supported(os),
+            MonitorPayload::Custom(info) => info.supported(os),
+            MonitorPayload::Plugin(info) => info.supported(os),
+            MonitorPayload::None => true,
         }
     }
 
-    fn parse_from_str(&mut self, s: &str) -> anyhow::Result<()> {
+    fn exec_cmd(&self, config: &MonitorExecConfig) -> String {
         match self {
-            MonitorPayload::Mem(info) => info.parse_from_str(s),
-            MonitorPayload::Cpu(info) => info.parse_from_str(s),
-            MonitorPayload::Disk(info) => info.parse_from_str(s),
-            MonitorPayload::Net(info) => info.parse_from_str(s),
+            MonitorPayload::Mem(info) => info.exec_cmd(config),
+            MonitorPayload::Cpu(info) => info.exec_cmd(config),
+            MonitorPayload::Disk(info) => info.exec_cmd(config),
+            MonitorPayload::Net(info) => info.exec_cmd(config),
+            MonitorPayload::DiskIo(info) => info.exec_cmd(config),
+            MonitorPayload::Load(info) => info.exec_cmd(config),
+            MonitorPayload::Pressure(info) => info.exec_cmd(config),
+            MonitorPayload::Process(info) => info.exec_cmd(config),
+            MonitorPayload::Systemd(info) => info.exec_cmd(config),
+            MonitorPayload::Sensors(info) => info.exec_cmd(config),
+            MonitorPayload::Sockets(info) => info.exec_cmd(config),
+            MonitorPayload::Custom(info) => info.exec_cmd(config),
+            MonitorPayload::Plugin(info) => info.exec_cmd(config),
+            MonitorPayload::None => String::new(),
+        }
+    }
+
+    fn parse_from_str(&mut self, s: &str, config: &MonitorExecConfig) -> anyhow::Result<()> {
+        match self {
+            MonitorPayload::Mem(info) => info.parse_from_str(s, config),
+            MonitorPayload::Cpu(info) => info.parse_from_str(s, config),
+            MonitorPayload::Disk(info) => info.parse_from_str(s, config),
+            MonitorPayload::Net(info) => info.parse_from_str(s, config),
+            MonitorPayload::DiskIo(info) => info.parse_from_str(s, config),
+            MonitorPayload::Load(info) => info.parse_from_str(s, config),
+            MonitorPayload::Pressure(info) => info.parse_from_str(s, config),
+            MonitorPayload::Process(info) => info.parse_from_str(s, config),
+            MonitorPayload::Systemd(info) => info.parse_from_str(s, config),
+            MonitorPayload::Sensors(info) => info.parse_from_str(s, config),
+            MonitorPayload::Sockets(info) => info.parse_from_str(s, config),
+            MonitorPayload::Custom(info) => info.parse_from_str(s, config),
+            MonitorPayload::Plugin(info) => info.parse_from_str(s, config),
             MonitorPayload::None => Ok(()),
         }
     }
 
+    fn apply_previous(&mut self, prev: &Self) {
+        match (self, prev) {
+            (MonitorPayload::Cpu(info), MonitorPayload::Cpu(prev)) => info.apply_previous(prev),
+            (MonitorPayload::Net(info), MonitorPayload::Net(prev)) => info.apply_previous(prev),
+            (MonitorPayload::DiskIo(info), MonitorPayload::DiskIo(prev)) => {
+                info.apply_previous(prev)
+            }
+            (MonitorPayload::Pressure(info), MonitorPayload::Pressure(prev)) => {
+                info.apply_previous(prev)
+            }
+            (MonitorPayload::Process(info), MonitorPayload::Process(prev)) => {
+                info.apply_previous(prev)
+            }
+            (MonitorPayload::Systemd(info), MonitorPayload::Systemd(prev)) => {
+                info.apply_previous(prev)
+            }
+            (MonitorPayload::Sockets(info), MonitorPayload::Sockets(prev)) => {
+                info.apply_previous(prev)
+            }
+            _ => {}
+        }
+    }
+
+    fn alerts(&self, prev: &Self) -> Vec<Alert> {
+        match (self, prev) {
+            (MonitorPayload::Load(info), MonitorPayload::Load(prev)) => info.alerts(prev),
+            (MonitorPayload::Pressure(info), MonitorPayload::Pressure(prev)) => info.alerts(prev),
+            (MonitorPayload::Process(info), MonitorPayload::Process(prev)) => info.alerts(prev),
+            (MonitorPayload::Sockets(info), MonitorPayload::Sockets(prev)) => info.alerts(prev),
+            _ => Vec::new(),
+        }
+    }
+
     fn common_display(&self) -> String {
         match self {
             MonitorPayload::Mem(info) => info.common_display(),
             MonitorPayload::Cpu(info) => info.common_display(),
             MonitorPayload::Disk(info) => info.common_display(),
             MonitorPayload::Net(info) => info.common_display(),
+            MonitorPayload::DiskIo(info) => info.common_display(),
+            MonitorPayload::Load(info) => info.common_display(),
+            MonitorPayload::Pressure(info) => info.common_display(),
+            MonitorPayload::Process(info) => info.common_display(),
+            MonitorPayload::Systemd(info) => info.common_display(),
+            MonitorPayload::Sensors(info) => info.common_display(),
+            MonitorPayload::Sockets(info) => info.common_display(),
+            MonitorPayload::Custom(info) => info.common_display(),
+            MonitorPayload::Plugin(info) => info.common_display(),
             MonitorPayload::None => String::from("No Data"),
         }
     }
 }
 
-#[derive(Debug)]
+/// 由相邻两次采样推断出的事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum Alert {
+    /// 远端重启：开机时长回退或开机标识变化
+    Reboot { uptime: f64 },
+    /// 两次采样之间 OOM killer 杀死了进程
+    OomKill { kills: u64 },
+    /// 监视的进程全部退出
+    ProcessGone { process: String, pid: u32 },
+    /// 监视的主进程 PID 变化
+    ProcessRestarted {
+        process: String,
+        old_pid: u32,
+        new_pid: u32,
+    },
+    /// 出现新的监听端口
+    PortOpened { socket: ListenSocket },
+    /// 监听端口消失
+    PortClosed { socket: ListenSocket },
+}
+
+impl std::fmt::Display for Alert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Alert::Reboot { uptime } => {
+                write!(f, "REBOOT: host restarted, up {}", format_duration(*uptime))
+            }
+            Alert::OomKill { kills } => {
+                write!(f, "OOM: {kills} process(es) killed by the OOM killer")
+            }
+            Alert::ProcessGone { process, pid } => {
+                write!(f, "PROCESS DOWN: {process} (last PID {pid})")
+            }
+            Alert::ProcessRestarted {
+                process,
+                old_pid,
+                new_pid,
+            } => write!(f, "PROCESS RESTARTED: {process} PID {old_pid} -> {new_pid}"),
+            Alert::PortOpened { socket } => write!(f, "PORT OPENED: {socket}"),
+            Alert::PortClosed { socket } => write!(f, "PORT CLOSED: {socket}"),
+        }
+    }
+}
+
+/// 错误事件的类别，UI 据此区分显示
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// 建立连接或认证失败
+    Connect,
+    /// 主机密钥与 known_hosts / 固定指纹不符
+    HostKeyMismatch,
+    /// 主机不在 known_hosts 中，或无法读取 known_hosts
+    HostKeyUnknown,
+    /// 监控命令执行或解析失败
+    Monitor,
+}
+
+#[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub enum MonitorEvent {
     Sample {
         server: String,
         kind: MonitorKind,
-        payload: MonitorPayload,
+        /// 各监控项的采样大小差别很大，装箱后事件本身保持较小
+        payload: Box<MonitorPayload>,
+        timestamp: DateTime<Utc>,
+    },
+    /// 由监控项 `kind` 的连续采样发现的事件
+    Alert {
+        server: String,
+        kind: MonitorKind,
+        alert: Alert,
         timestamp: DateTime<Utc>,
     },
     Error {
         server: String,
         kind: Option<MonitorKind>,
+        category: ErrorKind,
         error: String,
         timestamp: DateTime<Utc>,
     },
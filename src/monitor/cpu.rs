@@ -1,5 +1,27 @@
 use crate::Monitorable;
+use crate::monitor::{MonitorExecConfig, RemoteOs, ToolCaps, elapsed, parse_uptime, rate};
 use anyhow::Result;
+use serde::Deserialize;
+
+/// `[servers.monitor_opts.cpu]`
+#[derive(Debug, Deserialize, Clone)]
+pub struct CpuOpts {
+    /// 显示 CPU 占用最高的进程数
+    #[serde(default = "default_top_n")]
+    pub top_n: usize,
+}
+
+impl Default for CpuOpts {
+    fn default() -> Self {
+        Self {
+            top_n: default_top_n(),
+        }
+    }
+}
+
+fn default_top_n() -> usize {
+    10
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ProcessInfo {
@@ -9,53 +31,144 @@ pub struct ProcessInfo {
     pub mem_percent: f64,
 }
 
-#[derive(Debug, Clone, PartialEq, Default)]
-pub struct CpuInfo {
-    pub usage_percent: f64,
+/// `/proc/stat` 中一行 `cpu`/`cpuN` 的累计 jiffies
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CpuTimes {
     pub user: u64,
+    pub nice: u64,
     pub system: u64,
     pub idle: u64,
+    pub iowait: u64,
+    pub irq: u64,
+    pub softirq: u64,
+    pub steal: u64,
+    pub guest: u64,
+}
+
+impl CpuTimes {
+    /// 所有状态之和；内核已把 guest 计入 user，不重复累加
+    pub fn total(&self) -> u64 {
+        self.user
+            + self.nice
+            + self.system
+            + self.idle
+            + self.iowait
+            + self.irq
+            + self.softirq
+            + self.steal
+    }
+
+    pub fn idle_all(&self) -> u64 {
+        self.idle + self.iowait
+    }
+}
+
+/// 各状态在一段时间内所占的百分比
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CpuBreakdown {
+    pub user: f64,
+    pub nice: f64,
+    pub system: f64,
+    pub idle: f64,
+    pub iowait: f64,
+    pub irq: f64,
+    pub softirq: f64,
+    pub steal: f64,
+    /// 运行虚拟机的时间，已包含在 user 中
+    pub guest: f64,
+}
+
+impl CpuBreakdown {
+    /// 两次累计计数器之间的占比；计数器回退（远端重启）或没有变化时返回 None
+    pub fn between(cur: &CpuTimes, prev: &CpuTimes) -> Option<Self> {
+        if cur.total() <= prev.total() || cur.idle_all() < prev.idle_all() {
+            return None;
+        }
+        let total = (cur.total() - prev.total()) as f64;
+        let pct = |now: u64, before: u64| now.saturating_sub(before) as f64 / total * 100.0;
+        Some(Self {
+            user: pct(cur.user, prev.user),
+            nice: pct(cur.nice, prev.nice),
+            system: pct(cur.system, prev.system),
+            idle: pct(cur.idle, prev.idle),
+            iowait: pct(cur.iowait, prev.iowait),
+            irq: pct(cur.irq, prev.irq),
+            softirq: pct(cur.softirq, prev.softirq),
+            steal: pct(cur.steal, prev.steal),
+            guest: pct(cur.guest, prev.guest),
+        })
+    }
+
+    /// 非空闲时间占比，iowait 视为空闲
+    pub fn usage(&self) -> f64 {
+        (100.0 - self.idle - self.iowait).max(0.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CpuInfo {
+    pub usage_percent: f64,
+    pub breakdown: CpuBreakdown,
+    /// 每个核的使用率，按 cpuN 的顺序
+    pub core_usage: Vec<f64>,
+    /// 整体的累计计数器，用于与上一次采样求差
+    pub times: CpuTimes,
+    pub core_times: Vec<CpuTimes>,
+    /// 开机以来的上下文切换与中断次数，仅 Linux
+    pub ctxt: Option<u64>,
+    pub intr: Option<u64>,
+    /// 每秒上下文切换与中断次数
+    pub ctxt_rate: Option<f64>,
+    pub intr_rate: Option<f64>,
+    /// 采样时远端的时间戳（秒），见 [`RemoteOs::clock_cmd`]
+    pub uptime: f64,
     pub top_processes: Vec<ProcessInfo>,
 }
 
+/// 一次采样中读到的累计计数器
+#[derive(Default)]
+struct StatCounters {
+    times: CpuTimes,
+    cores: Vec<CpuTimes>,
+    ctxt: Option<u64>,
+    intr: Option<u64>,
+}
+
+/// 用于 [`CpuInfo::core_strip`] 的字符，由低到高
+const STRIP_LEVELS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
 impl CpuInfo {
-    fn parse_stat_line(line: &str) -> Option<(u64, u64, u64, u64, u64, u64, u64)> {
+    /// 解析 `cpu`/`cpuN` 行；较老的内核没有 steal/guest 列，按 0 处理
+    fn parse_stat_line(line: &str) -> Option<CpuTimes> {
         let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 8 || parts[0] != "cpu" {
+        if parts.len() < 8 || !parts[0].starts_with("cpu") {
             return None;
         }
-        Some((
-            parts[1].parse().ok()?, // user
-            parts[2].parse().ok()?, // nice
-            parts[3].parse().ok()?, // system
-            parts[4].parse().ok()?, // idle
-            parts[5].parse().ok()?, // iowait
-            parts[6].parse().ok()?, // irq
-            parts[7].parse().ok()?, // softirq
-        ))
-    }
-
-    fn read_cpu_total_idle(s: &str) -> Option<(u64, u64)> {
-        for line in s.lines() {
-            if line.starts_with("cpu ") {
-                let (user, nice, system, idle, iowait, irq, softirq) = Self::parse_stat_line(line)?;
-                let total = user + nice + system + idle + iowait + irq + softirq;
-                let idle_all = idle + iowait;
-                return Some((total, idle_all));
+        let field = |idx: usize| -> Option<u64> {
+            match parts.get(idx) {
+                Some(v) => v.parse().ok(),
+                None => Some(0),
             }
-        }
-        None
+        };
+        Some(CpuTimes {
+            user: field(1)?,
+            nice: field(2)?,
+            system: field(3)?,
+            idle: field(4)?,
+            iowait: field(5)?,
+            irq: field(6)?,
+            softirq: field(7)?,
+            steal: field(8)?,
+            guest: field(9)?,
+        })
     }
 
-    fn calc_usage(first: &str, second: &str) -> Option<f64> {
-        let (t1, i1) = Self::read_cpu_total_idle(first)?;
-        let (t2, i2) = Self::read_cpu_total_idle(second)?;
-        let total_diff = t2.saturating_sub(t1);
-        let idle_diff = i2.saturating_sub(i1);
-        if total_diff == 0 {
-            return Some(0.0);
-        }
-        Some(((total_diff - idle_diff) as f64 / total_diff as f64) * 100.0)
+    /// 每个核一个字符的使用率条，如 `▂▅█▁`
+    pub fn core_strip(&self) -> String {
+        self.core_usage
+            .iter()
+            .map(|u| STRIP_LEVELS[((u / 100.0 * 8.0).round() as usize).min(8)])
+            .collect()
     }
 
     /// 解析 ps 输出的进程信息
@@ -83,52 +196,313 @@ impl CpuInfo {
         }
         result
     }
-}
 
-impl Monitorable for CpuInfo {
-    fn exec_cmd(&self) -> &'static str {
-        // 一次执行：两次采样 + ps 输出
-        "cat /proc/stat; sleep 0.2; cat /proc/stat; echo '---'; ps -eo pid,comm,%cpu,%mem --sort=-%cpu | head -n 11"
+    /// 解析 BSD `ps -o pid,%cpu,%mem,comm`：comm 放在最后，可能是带空格的完整路径
+    fn parse_bsd_processes(ps_output: &str) -> Vec<ProcessInfo> {
+        let mut result = Vec::new();
+        for line in ps_output.lines().skip(1) {
+            let mut cols = line.split_whitespace();
+            let (Some(pid), Some(cpu), Some(mem)) = (cols.next(), cols.next(), cols.next()) else {
+                continue;
+            };
+            let comm = cols.collect::<Vec<_>>().join(" ");
+            if let (Ok(pid), Ok(cpu), Ok(mem)) =
+                (pid.parse::<u32>(), cpu.parse::<f64>(), mem.parse::<f64>())
+            {
+                result.push(ProcessInfo {
+                    pid,
+                    name: comm.rsplit('/').next().unwrap_or(&comm).to_string(),
+                    cpu_percent: cpu,
+                    mem_percent: mem,
+                });
+            }
+        }
+        result
     }
 
-    fn parse_from_str(&mut self, s: &str) -> Result<()> {
-        // 分割 /proc/stat 和 ps 输出
-        let mut parts = s.split("---");
-        let stat_part = parts
+    /// 不依赖 ps，直接由 `/proc/[pid]/stat` 计算进程的 CPU 与内存占用。
+    ///
+    /// 与 ps 的 %CPU 一样取进程生命周期内的平均值；时钟频率与页大小取自连接时的探测。
+    /// 输入首行为 `/proc/meminfo` 的 MemTotal，其余每行一个进程。
+    fn parse_proc_processes(s: &str, uptime: f64, caps: &ToolCaps) -> Vec<ProcessInfo> {
+        let hz = caps.clk_tck as f64;
+        let page_kb = caps.page_kb as f64;
+        let mut lines = s.lines();
+        let mem_total_kb = lines
             .next()
-            .ok_or_else(|| anyhow::anyhow!("missing stat"))?;
-        let ps_part = parts.next().unwrap_or("");
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.0);
 
-        let stat_sections: Vec<&str> = stat_part.split("cpu ").collect();
-        if stat_sections.len() < 3 {
-            anyhow::bail!("unexpected /proc/stat output");
+        let mut result = Vec::new();
+        for line in lines {
+            // comm 可能包含空格与括号，以最后一个 ')' 为界
+            let (Some(open), Some(close)) = (line.find('('), line.rfind(')')) else {
+                continue;
+            };
+            let Ok(pid) = line[..open].trim().parse::<u32>() else {
+                continue;
+            };
+            // 从第 3 个字段 state 开始
+            let fields: Vec<&str> = line[close + 1..].split_whitespace().collect();
+            let field = |n: usize| -> Option<f64> { fields.get(n - 3)?.parse().ok() };
+            let (Some(utime), Some(stime), Some(start), Some(rss)) =
+                (field(14), field(15), field(22), field(24))
+            else {
+                continue;
+            };
+            let elapsed = uptime - start / hz;
+            let cpu_percent = if elapsed > 0.0 {
+                (utime + stime) / hz / elapsed * 100.0
+            } else {
+                0.0
+            };
+            let mem_percent = if mem_total_kb > 0.0 {
+                rss * page_kb / mem_total_kb * 100.0
+            } else {
+                0.0
+            };
+            result.push(ProcessInfo {
+                pid,
+                name: line[open + 1..close].to_string(),
+                cpu_percent,
+                mem_percent,
+            });
         }
+        result.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent));
+        result
+    }
+
+    /// 计数器部分
+    fn parse_counters(os: RemoteOs, stat_part: &str) -> Result<StatCounters> {
+        match os {
+            RemoteOs::Linux => {
+                let mut total = None;
+                let mut cores = Vec::new();
+                let (mut ctxt, mut intr) = (None, None);
+                for line in stat_part.lines() {
+                    let first_value = || line.split_whitespace().nth(1)?.parse().ok();
+                    if line.starts_with("cpu ") {
+                        total = CpuInfo::parse_stat_line(line);
+                    } else if line.starts_with("cpu") {
+                        cores.extend(CpuInfo::parse_stat_line(line));
+                    } else if line.starts_with("ctxt ") {
+                        ctxt = first_value();
+                    } else if line.starts_with("intr ") {
+                        // 首个数字是总数，其后是各中断号的计数
+                        intr = first_value();
+                    }
+                }
+                let total = total.ok_or_else(|| anyhow::anyhow!("failed to parse cpu line"))?;
+                Ok(StatCounters {
+                    times: total,
+                    cores,
+                    ctxt,
+                    intr,
+                })
+            }
+            RemoteOs::FreeBsd => {
+                // kern.cp_time: user nice sys intr idle；kern.cp_times 依次是每个核的同样 5 列
+                let ticks = |idx: usize| -> Vec<u64> {
+                    stat_part
+                        .lines()
+                        .nth(idx)
+                        .unwrap_or("")
+                        .split_whitespace()
+                        .filter_map(|v| v.parse().ok())
+                        .collect()
+                };
+                let from_cp = |cp: &[u64]| CpuTimes {
+                    user: cp[0],
+                    nice: cp[1],
+                    system: cp[2],
+                    irq: cp[3],
+                    idle: cp[4],
+                    ..CpuTimes::default()
+                };
+                let total = ticks(1);
+                if total.len() != 5 {
+                    anyhow::bail!("failed to parse kern.cp_time");
+                }
+                let cores = ticks(2).chunks_exact(5).map(from_cp).collect();
+                Ok(StatCounters {
+                    times: from_cp(&total),
+                    cores,
+                    ..StatCounters::default()
+                })
+            }
+            // macOS 没有累计计数器，见 [`CpuInfo::parse_top_usage`]
+            RemoteOs::MacOs => Ok(StatCounters::default()),
+        }
+    }
 
-        let first = format!("cpu {}", stat_sections[1]);
-        let second = format!("cpu {}", stat_sections[2]);
+    /// macOS top 的结果："CPU usage: 5.26% user, 10.52% sys, 84.21% idle"。
+    ///
+    /// macOS 没有 `kern.cp_time` 这样的累计计数器，top 也只输出百分比，无法在相邻两次
+    /// 采样间求差。因此每轮在远端后台运行一次间隔 1 秒的 top，下一轮读取其结果；
+    /// 首轮还没有结果时退回 `top -l 1`，即开机以来的平均值。
+    fn parse_top_usage(stat_part: &str) -> Result<CpuBreakdown> {
+        let line = stat_part
+            .lines()
+            .rev()
+            .find_map(|line| line.strip_prefix("CPU usage:"))
+            .ok_or_else(|| anyhow::anyhow!("failed to parse top CPU usage"))?;
+        let field = |name: &str| {
+            line.split(',')
+                .find_map(|f| f.trim().strip_suffix(name))
+                .and_then(|v| v.trim_end_matches('%').trim().parse::<f64>().ok())
+        };
+        Ok(CpuBreakdown {
+            user: field(" user").unwrap_or(0.0),
+            system: field(" sys").unwrap_or(0.0),
+            idle: field(" idle").ok_or_else(|| anyhow::anyhow!("failed to parse top CPU usage"))?,
+            ..CpuBreakdown::default()
+        })
+    }
+}
 
-        let usage = CpuInfo::calc_usage(&first, &second)
-            .ok_or_else(|| anyhow::anyhow!("failed to compute CPU usage"))?;
+impl Monitorable for CpuInfo {
+    fn exec_cmd(&self, config: &MonitorExecConfig) -> String {
+        // 单次读取计数器，使用率由相邻两次采样求差得到；head 多取一行标题
+        let lines = config.opts.cpu.top_n + 1;
+        let clock = config.os.clock_cmd();
+        match config.os {
+            // BusyBox ps 不支持 -o/--sort，直接读取每个进程的 stat
+            RemoteOs::Linux if !config.caps.gnu_ps => format!(
+                "{clock}; cat /proc/stat; echo '---'; head -n 1 /proc/meminfo; cat /proc/[0-9]*/stat 2>/dev/null"
+            ),
+            RemoteOs::Linux => format!(
+                "{clock}; cat /proc/stat; echo '---'; ps -eo pid,comm,%cpu,%mem --sort=-%cpu | head -n {lines}"
+            ),
+            // 见 [`CpuInfo::parse_top_usage`]：读取上一轮在后台采到的结果，再启动下一次采样，
+            // 批量命令不必等待 top 的 1 秒间隔
+            RemoteOs::MacOs => format!(
+                "{clock}; f=\"${{TMPDIR:-/tmp}}/stalking-cpu.$(id -u)\"; \
+                 cat \"$f\" 2>/dev/null || top -l 1 -n 0 | grep '^CPU usage'; \
+                 (top -l 2 -n 0 -s 1 | grep '^CPU usage' | tail -n 1 > \"$f.$$\" && mv \"$f.$$\" \"$f\") \
+                 </dev/null >/dev/null 2>&1 & \
+                 echo '---'; ps -Ao pid,%cpu,%mem,comm -r | head -n {lines}"
+            ),
+            RemoteOs::FreeBsd => format!(
+                "{clock}; sysctl -n kern.cp_time kern.cp_times; echo '---'; ps -axo pid,%cpu,%mem,comm -r | head -n {lines}"
+            ),
+        }
+    }
 
-        let (user, _nice, system, idle, _, _, _) =
-            CpuInfo::parse_stat_line(second.lines().next().unwrap())
-                .ok_or_else(|| anyhow::anyhow!("failed to parse cpu line"))?;
+    fn parse_from_str(&mut self, s: &str, config: &MonitorExecConfig) -> Result<()> {
+        // 分割 /proc/stat 和 ps 输出；进程名里也可能出现 "---"，只按独占一行的分隔符切分
+        let (stat_part, ps_part) = s.split_once("\n---\n").unwrap_or((s, ""));
 
-        let top = CpuInfo::parse_top_processes(ps_part);
+        let uptime = parse_uptime(stat_part)?;
+        // 首次采样没有参照，先给出开机以来的平均值
+        let StatCounters {
+            times,
+            cores: core_times,
+            ctxt,
+            intr,
+        } = CpuInfo::parse_counters(config.os, stat_part)?;
+        let breakdown = match config.os {
+            RemoteOs::MacOs => CpuInfo::parse_top_usage(stat_part)?,
+            _ => CpuBreakdown::between(&times, &CpuTimes::default()).unwrap_or_default(),
+        };
+        let core_usage = core_times
+            .iter()
+            .map(|c| CpuBreakdown::between(c, &CpuTimes::default()).map_or(0.0, |b| b.usage()))
+            .collect();
+        let since_boot = |count: Option<u64>| match config.os {
+            RemoteOs::Linux if uptime > 0.0 => count.map(|c| c as f64 / uptime),
+            _ => None,
+        };
+        let top_processes = match config.os {
+            RemoteOs::Linux if !config.caps.gnu_ps => {
+                CpuInfo::parse_proc_processes(ps_part, uptime, &config.caps)
+            }
+            RemoteOs::Linux => CpuInfo::parse_top_processes(ps_part),
+            RemoteOs::MacOs | RemoteOs::FreeBsd => CpuInfo::parse_bsd_processes(ps_part),
+        };
 
         *self = CpuInfo {
-            usage_percent: usage,
-            user,
-            system,
-            idle,
-            top_processes: top,
+            usage_percent: breakdown.usage(),
+            breakdown,
+            core_usage,
+            times,
+            core_times,
+            ctxt,
+            intr,
+            ctxt_rate: since_boot(ctxt),
+            intr_rate: since_boot(intr),
+            uptime,
+            top_processes: top_processes
+                .into_iter()
+                .take(config.opts.cpu.top_n)
+                .collect(),
         };
 
         Ok(())
     }
 
+    fn apply_previous(&mut self, prev: &Self) {
+        // 远端重启后计数器归零，此时保留开机以来的平均值；
+        // BSD 的时间戳可能只有秒级精度，因此允许相等
+        if self.uptime < prev.uptime {
+            return;
+        }
+        if let Some(breakdown) = CpuBreakdown::between(&self.times, &prev.times) {
+            self.breakdown = breakdown;
+            self.usage_percent = breakdown.usage();
+        }
+        if self.core_times.len() == prev.core_times.len() {
+            for ((usage, cur), old) in self
+                .core_usage
+                .iter_mut()
+                .zip(&self.core_times)
+                .zip(&prev.core_times)
+            {
+                if let Some(b) = CpuBreakdown::between(cur, old) {
+                    *usage = b.usage();
+                }
+            }
+        }
+        if let Some(dt) = elapsed(self.uptime, prev.uptime) {
+            let per_sec = |cur: Option<u64>, old: Option<u64>| rate(cur?, old?, dt);
+            if let Some(r) = per_sec(self.ctxt, prev.ctxt) {
+                self.ctxt_rate = Some(r);
+            }
+            if let Some(r) = per_sec(self.intr, prev.intr) {
+                self.intr_rate = Some(r);
+            }
+        }
+    }
+
     fn common_display(&self) -> String {
-        let mut s = format!("CPU Usage: {:.2}%\nTop 10 processes:\n", self.usage_percent);
+        let b = &self.breakdown;
+        let mut s = format!(
+            "CPU Usage: {:.2}%\n  user {:.1}% nice {:.1}% system {:.1}% idle {:.1}% iowait {:.1}% \
+             irq {:.1}% softirq {:.1}% steal {:.1}% guest {:.1}%\n",
+            self.usage_percent,
+            b.user,
+            b.nice,
+            b.system,
+            b.idle,
+            b.iowait,
+            b.irq,
+            b.softirq,
+            b.steal,
+            b.guest
+        );
+        if !self.core_usage.is_empty() {
+            s.push_str(&format!(
+                "Cores ({}): [{}]\n",
+                self.core_usage.len(),
+                self.core_strip()
+            ));
+        }
+        if let (Some(ctxt), Some(intr)) = (self.ctxt_rate, self.intr_rate) {
+            s.push_str(&format!(
+                "Context switches: {ctxt:.0}/s, interrupts: {intr:.0}/s\n"
+            ));
+        }
+        s.push_str(&format!("Top {} processes:\n", self.top_processes.len()));
         for p in &self.top_processes {
             s.push_str(&format!(
                 "  {:<10} {:<20} {:>5.1}% CPU {:>5.1}% MEM\n",
@@ -0,0 +1,281 @@
+use crate::Monitorable;
+use crate::monitor::MonitorExecConfig;
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// `[[custom_monitors]]`：由配置定义的监控项，在 `monitors` 中以 `{ Custom = "<name>" }` 引用。
+///
+/// ```toml
+/// [[custom_monitors]]
+/// name = "redis"
+/// command = "redis-cli info stats"
+/// parser = "KeyValue"                      # 或 "Number"、{ JsonPath = "a.b" }、{ Regex = '...' }
+/// unit = "ops"
+/// fields.instantaneous_ops_per_sec = { label = "ops/s", unit = "" }
+/// ```
+#[derive(Debug, Deserialize, Clone)]
+pub struct CustomMonitor {
+    pub name: String,
+    /// 在远端执行的命令，原样交给 shell
+    pub command: String,
+    pub parser: CustomParser,
+    /// 页面标题，缺省为 name
+    pub label: Option<String>,
+    /// 各字段的默认单位
+    #[serde(default)]
+    pub unit: String,
+    /// 按字段名覆盖显示名与单位
+    #[serde(default)]
+    pub fields: HashMap<String, FieldDisplay>,
+    /// 采样间隔，缺省使用服务器的 `poll_interval_ms`
+    pub interval_ms: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct FieldDisplay {
+    pub label: Option<String>,
+    pub unit: Option<String>,
+}
+
+/// 命令输出的解析方式
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub enum CustomParser {
+    /// 整个输出是一个数字
+    Number,
+    /// 每行 `key=value` 或 `key:value`，忽略空行与 `#` 开头的行
+    KeyValue,
+    /// 取 JSON 中 `a.b[0].c` 处的值；若是对象则展开其中的标量字段
+    JsonPath(String),
+    /// 正则的命名捕获组作为字段，只取第一处匹配
+    Regex(String),
+}
+
+impl CustomMonitor {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty()
+            || !self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_-".contains(c))
+        {
+            anyhow::bail!(
+                "Custom monitor name {:?} may only contain letters, digits, '_' and '-'",
+                self.name
+            );
+        }
+        if self.command.trim().is_empty() {
+            anyhow::bail!("Custom monitor {} has an empty command", self.name);
+        }
+        if let CustomParser::Regex(pattern) = &self.parser {
+            let re = regex::Regex::new(pattern)
+                .map_err(|e| anyhow::anyhow!("Custom monitor {}: {e}", self.name))?;
+            if re.capture_names().flatten().next().is_none() {
+                anyhow::bail!(
+                    "Custom monitor {} regex has no named capture groups",
+                    self.name
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn label(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.name)
+    }
+
+    fn metric(&self, key: String, value: MetricValue) -> Metric {
+        let display = self.fields.get(&key);
+        Metric {
+            label: display
+                .and_then(|d| d.label.clone())
+                .unwrap_or_else(|| key.clone()),
+            unit: display
+                .and_then(|d| d.unit.clone())
+                .unwrap_or_else(|| self.unit.clone()),
+            key,
+            value,
+        }
+    }
+
+    /// 按 `parser` 把命令输出转换成字段列表
+    pub fn parse(&self, s: &str) -> Result<Vec<Metric>> {
+        let metrics = match &self.parser {
+            CustomParser::Number => {
+                let value = s
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|_| anyhow::anyhow!("expected a number, got {:?}", s.trim()))?;
+                vec![self.metric(self.name.clone(), MetricValue::Number(value))]
+            }
+            CustomParser::KeyValue => s
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .filter_map(|line| {
+                    let idx = line.find(['=', ':'])?;
+                    let key = line[..idx].trim().to_string();
+                    Some(self.metric(key, MetricValue::parse(&line[idx + 1..])))
+                })
+                .collect(),
+            CustomParser::JsonPath(path) => {
+                let root: Value = serde_json::from_str(s)?;
+                let value = json_lookup(&root, path)
+                    .ok_or_else(|| anyhow::anyhow!("JSON path {path:?} not found"))?;
+                match value {
+                    Value::Object(map) => map
+                        .iter()
+                        .filter_map(|(k, v)| {
+                            Some(self.metric(k.clone(), MetricValue::from_json(v)?))
+                        })
+                        .collect(),
+                    Value::Array(items) => items
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(i, v)| {
+                            Some(self.metric(i.to_string(), MetricValue::from_json(v)?))
+                        })
+                        .collect(),
+                    scalar => {
+                        let value = MetricValue::from_json(scalar)
+                            .ok_or_else(|| anyhow::anyhow!("JSON path {path:?} is null"))?;
+                        vec![self.metric(self.name.clone(), value)]
+                    }
+                }
+            }
+            CustomParser::Regex(pattern) => {
+                let re = regex::Regex::new(pattern)?;
+                let caps = re
+                    .captures(s)
+                    .ok_or_else(|| anyhow::anyhow!("regex did not match output"))?;
+                re.capture_names()
+                    .flatten()
+                    .filter_map(|name| {
+                        let value = caps.name(name)?.as_str();
+                        Some(self.metric(name.to_string(), MetricValue::parse(value)))
+                    })
+                    .collect()
+            }
+        };
+        if metrics.is_empty() {
+            anyhow::bail!("no fields found in output");
+        }
+        Ok(metrics)
+    }
+}
+
+/// 按 `a.b[0].c` 查找 JSON 中的值，可带 `$.` 前缀
+fn json_lookup<'a>(root: &'a Value, path: &str) -> Option<&'a Value> {
+    let path = path.trim_start_matches('$').trim_start_matches('.');
+    let mut current = root;
+    for segment in path.split('.').filter(|s| !s.is_empty()) {
+        let (key, indexes) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+        if !key.is_empty() {
+            current = current.get(key)?;
+        }
+        for index in indexes.split('[').skip(1) {
+            let index: usize = index.strip_suffix(']')?.parse().ok()?;
+            current = current.get(index)?;
+        }
+    }
+    Some(current)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetricValue {
+    Number(f64),
+    Text(String),
+}
+
+impl MetricValue {
+    fn parse(s: &str) -> Self {
+        let s = s.trim();
+        match s.parse::<f64>() {
+            Ok(v) => MetricValue::Number(v),
+            Err(_) => MetricValue::Text(s.to_string()),
+        }
+    }
+
+    pub(crate) fn from_json(value: &Value) -> Option<Self> {
+        match value {
+            Value::Number(n) => n.as_f64().map(MetricValue::Number),
+            Value::String(s) => Some(MetricValue::Text(s.clone())),
+            Value::Bool(b) => Some(MetricValue::Text(b.to_string())),
+            Value::Null | Value::Array(_) | Value::Object(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for MetricValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            // 整数不显示小数部分
+            MetricValue::Number(v) if v.fract() == 0.0 && v.abs() < 1e15 => write!(f, "{v:.0}"),
+            MetricValue::Number(v) => write!(f, "{v:.2}"),
+            MetricValue::Text(s) => f.write_str(s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metric {
+    pub key: String,
+    pub label: String,
+    pub value: MetricValue,
+    pub unit: String,
+}
+
+/// 自定义监控项的一次采样：通用的字段列表
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CustomInfo {
+    pub name: String,
+    pub label: String,
+    pub metrics: Vec<Metric>,
+}
+
+impl CustomInfo {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            label: name.to_string(),
+            metrics: Vec::new(),
+        }
+    }
+}
+
+impl Monitorable for CustomInfo {
+    fn exec_cmd(&self, config: &MonitorExecConfig) -> String {
+        // 未定义的监控项在加载配置时就会报错，这里只是兜底
+        config
+            .opts
+            .custom(&self.name)
+            .map(|m| m.command.clone())
+            .unwrap_or_else(|| "false".to_string())
+    }
+
+    fn parse_from_str(&mut self, s: &str, config: &MonitorExecConfig) -> Result<()> {
+        let monitor = config
+            .opts
+            .custom(&self.name)
+            .ok_or_else(|| anyhow::anyhow!("custom monitor {} is not defined", self.name))?;
+        self.label = monitor.label().to_string();
+        self.metrics = monitor.parse(s)?;
+        Ok(())
+    }
+
+    fn common_display(&self) -> String {
+        display_metrics(&self.label, &self.metrics)
+    }
+}
+
+/// 自定义监控项与插件共用的显示格式：每个字段一行
+pub(crate) fn display_metrics(label: &str, metrics: &[Metric]) -> String {
+    let mut s = format!("{label}:\n");
+    for m in metrics {
+        let line = format!("  {:<24} {} {}", m.label, m.value, m.unit);
+        s.push_str(line.trim_end());
+        s.push('\n');
+    }
+    s
+}
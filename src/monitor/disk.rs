@@ -1,5 +1,102 @@
 use crate::Monitorable;
+use crate::monitor::{MonitorExecConfig, RemoteOs};
 use anyhow::Result;
+use serde::Deserialize;
+
+/// `[servers.monitor_opts.disk]`
+#[derive(Debug, Deserialize, Clone)]
+pub struct DiskOpts {
+    /// Filesystem types passed to `df -x`
+    #[serde(default = "default_exclude_fs")]
+    pub exclude_fs: Vec<String>,
+}
+
+impl Default for DiskOpts {
+    fn default() -> Self {
+        Self {
+            exclude_fs: default_exclude_fs(),
+        }
+    }
+}
+
+impl DiskOpts {
+    pub fn validate(&self) -> Result<()> {
+        // the names end up in a shell command line
+        for fs in &self.exclude_fs {
+            if fs.is_empty()
+                || !fs
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "._-".contains(c))
+            {
+                anyhow::bail!("invalid filesystem type in exclude_fs: {fs:?}");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Mount points in /proc/mounts whose type is in `exclude_fs`.
+///
+/// Lines look like `tmpfs /run tmpfs rw,nosuid 0 0`; spaces in paths are escaped as `\040`.
+fn excluded_mount_points(mounts: &str, exclude_fs: &[String]) -> Vec<String> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut cols = line.split_whitespace();
+            let (_, mount_point, fs_type) = (cols.next()?, cols.next()?, cols.next()?);
+            exclude_fs
+                .iter()
+                .any(|fs| fs == fs_type)
+                .then(|| mount_point.replace("\\040", " "))
+        })
+        .collect()
+}
+
+fn default_exclude_fs() -> Vec<String> {
+    // devfs/autofs are the pseudo filesystems on macOS and FreeBSD
+    ["tmpfs", "devtmpfs", "devfs", "autofs"]
+        .map(String::from)
+        .to_vec()
+}
+
+/// Inode counts from `df -Pi` (GNU/BusyBox) or `df -ki` (BSD), keyed by mount point.
+///
+/// GNU prints `Filesystem Inodes IUsed IFree IUse% Mounted on`; BSD keeps the block
+/// columns and appends `iused ifree %iused` before the mount point.
+fn parse_inodes(df: &str) -> Vec<(String, u64, u64)> {
+    let mut lines = df.lines();
+    let bsd = lines
+        .next()
+        .is_some_and(|header| header.split_whitespace().any(|c| c == "iused"));
+    lines
+        .filter_map(|line| {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            let (total, used, mount_at) = if bsd {
+                let used: u64 = cols.get(5)?.parse().ok()?;
+                let free: u64 = cols.get(6)?.parse().ok()?;
+                (used + free, used, 8)
+            } else {
+                (cols.get(1)?.parse().ok()?, cols.get(2)?.parse().ok()?, 5)
+            };
+            let mount_point = cols.get(mount_at..)?.join(" ");
+            (!mount_point.is_empty()).then_some((mount_point, total, used))
+        })
+        .collect()
+}
+
+/// Splits the command output at the `---` lines between the `df` runs and /proc/mounts.
+fn split_sections(s: &str) -> Vec<String> {
+    let mut sections = vec![String::new()];
+    for line in s.lines() {
+        if line.trim() == "---" {
+            sections.push(String::new());
+        } else if let Some(section) = sections.last_mut() {
+            section.push_str(line);
+            section.push('\n');
+        }
+    }
+    sections
+}
 
 /// Parsed info from `df -P` (POSIX format).
 #[derive(Debug, Clone, Default)]
@@ -16,6 +113,19 @@ pub struct MountEntry {
     pub avail_kb: u64,
     pub use_percent: f64,
     pub mount_point: String,
+    /// Inode counts; zero when the filesystem has no fixed inode table (btrfs, vfat)
+    /// or the remote `df` cannot report them
+    pub inodes_total: u64,
+    pub inodes_used: u64,
+    pub inode_use_percent: Option<f64>,
+}
+
+impl MountEntry {
+    /// Whether inodes are closer to running out than blocks
+    pub fn inodes_worse(&self) -> bool {
+        self.inode_use_percent
+            .is_some_and(|inode| inode > self.use_percent)
+    }
 }
 
 impl DiskInfo {
@@ -39,6 +149,13 @@ impl DiskInfo {
         self.filesystems.iter().map(|e| e.used_kb * 1024).sum()
     }
 
+    /// Total and used inodes over filesystems that report them
+    pub fn inode_totals(&self) -> (u64, u64) {
+        self.filesystems.iter().fold((0, 0), |(total, used), e| {
+            (total + e.inodes_total, used + e.inodes_used)
+        })
+    }
+
     /// Total available disk size in bytes
     pub fn avail_bytes(&self) -> u64 {
         self.filesystems.iter().map(|e| e.avail_kb * 1024).sum()
@@ -46,15 +163,47 @@ impl DiskInfo {
 }
 
 impl Monitorable for DiskInfo {
-    fn exec_cmd(&self) -> &'static str {
-        // POSIX format, easier to parse
-        "df -P -x tmpfs -x devtmpfs"
+    fn exec_cmd(&self, config: &MonitorExecConfig) -> String {
+        // POSIX format, easier to parse; the second run reports inodes
+        let exclude = &config.opts.disk.exclude_fs;
+        match config.os {
+            // BusyBox df has no -x: list everything and filter by /proc/mounts.
+            // -i needs FEATURE_DF_FANCY, so a failure just leaves inodes out
+            RemoteOs::Linux if !config.caps.gnu_df => {
+                "df -P; echo '---'; df -Pi 2>/dev/null; echo '---'; cat /proc/mounts".to_string()
+            }
+            RemoteOs::Linux => {
+                let mut excludes = String::new();
+                for fs in exclude {
+                    excludes.push_str(" -x ");
+                    excludes.push_str(fs);
+                }
+                format!("df -P{excludes}; echo '---'; df -Pi{excludes}")
+            }
+            // BSD df takes one type list, negated as a whole by a "no" prefix;
+            // macOS spells the flag -T, FreeBSD -t
+            RemoteOs::MacOs | RemoteOs::FreeBsd if exclude.is_empty() => {
+                "df -kP; echo '---'; df -ki".to_string()
+            }
+            RemoteOs::MacOs => {
+                let types = exclude.join(",");
+                format!("df -kP -T no{types}; echo '---'; df -ki -T no{types}")
+            }
+            RemoteOs::FreeBsd => {
+                let types = exclude.join(",");
+                format!("df -kP -t no{types}; echo '---'; df -ki -t no{types}")
+            }
+        }
     }
 
-    fn parse_from_str(&mut self, s: &str) -> Result<()> {
+    fn parse_from_str(&mut self, s: &str, config: &MonitorExecConfig) -> Result<()> {
+        let sections = split_sections(s);
+        let df = &sections[0];
+        let inodes = sections.get(1).map(|s| parse_inodes(s)).unwrap_or_default();
+        let mounts = sections.get(2);
         let mut entries = Vec::new();
 
-        for (i, line) in s.lines().enumerate() {
+        for (i, line) in df.lines().enumerate() {
             let line = line.trim();
             if line.is_empty() || i == 0 {
                 // skip header
@@ -75,7 +224,15 @@ impl Monitorable for DiskInfo {
             let use_percent_str = cols[4].trim_end_matches('%');
             let use_percent = use_percent_str.parse::<f64>().unwrap_or(0.0);
 
-            let mount_point = cols[5].to_string();
+            // mount points may contain spaces (e.g. "/Volumes/Macintosh HD")
+            let mount_point = cols[5..].join(" ");
+
+            let (inodes_total, inodes_used) = inodes
+                .iter()
+                .find(|(mount, _, _)| *mount == mount_point)
+                .map_or((0, 0), |(_, total, used)| (*total, *used));
+            let inode_use_percent =
+                (inodes_total > 0).then(|| inodes_used as f64 / inodes_total as f64 * 100.0);
 
             entries.push(MountEntry {
                 filesystem: fs,
@@ -84,8 +241,15 @@ impl Monitorable for DiskInfo {
                 avail_kb,
                 use_percent,
                 mount_point,
+                inodes_total,
+                inodes_used,
+                inode_use_percent,
             });
         }
+        if let Some(mounts) = mounts {
+            let excluded = excluded_mount_points(mounts, &config.opts.disk.exclude_fs);
+            entries.retain(|e| !excluded.contains(&e.mount_point));
+        }
         self.filesystems = entries;
         Ok(())
     }
@@ -105,10 +269,29 @@ impl Monitorable for DiskInfo {
             total_gb, used_gb, used_pct, avail_gb
         );
 
-        summary.push_str("Mount Points:\n");
+        let (inodes_total, inodes_used) = self.inode_totals();
+        if inodes_total > 0 {
+            summary.push_str(&format!(
+                "Total Inodes: {}, Used: {} ({:.2}%)\n",
+                inodes_total,
+                inodes_used,
+                inodes_used as f64 / inodes_total as f64 * 100.0
+            ));
+        }
+
+        summary.push_str("Mount Points (* marks the fuller of blocks and inodes):\n");
         for e in &self.filesystems {
+            let (block_flag, inode_flag) = match e.inode_use_percent {
+                Some(_) if e.inodes_worse() => ("", "*"),
+                Some(_) => ("*", ""),
+                None => ("", ""),
+            };
+            let inodes = match e.inode_use_percent {
+                Some(pct) => format!("inodes ({pct:>5.1}%){inode_flag}"),
+                None => "inodes (    -  )".to_string(),
+            };
             summary.push_str(&format!(
-                "  {:<15} {:>6.1}G used ({:>5.1}%), mount: {}\n",
+                "  {:<15} {:>6.1}G used ({:>5.1}%){block_flag} {inodes}, mount: {}\n",
                 e.filesystem,
                 e.used_kb as f64 / 1_048_576.0,
                 e.use_percent,
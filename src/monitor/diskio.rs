@@ -0,0 +1,208 @@
+use crate::Monitorable;
+use crate::monitor::{MonitorExecConfig, RemoteOs, elapsed, parse_uptime, rate};
+use anyhow::Result;
+use serde::Deserialize;
+
+/// `[servers.monitor_opts.diskio]`
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct DiskIoOpts {
+    /// 是否显示分区（如 sda1、nvme0n1p2），默认只显示整块设备
+    #[serde(default)]
+    pub include_partitions: bool,
+    /// 是否显示 loop 与 ram 设备
+    #[serde(default)]
+    pub include_loop: bool,
+    /// 只显示这些设备，为空时显示全部
+    #[serde(default)]
+    pub devices: Vec<String>,
+    /// 不显示的设备
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl DiskIoOpts {
+    fn wants(&self, name: &str, whole_disk: bool) -> bool {
+        if !self.devices.is_empty() {
+            return self.devices.iter().any(|d| d == name);
+        }
+        (whole_disk || self.include_partitions)
+            && (self.include_loop || !(name.starts_with("loop") || name.starts_with("ram")))
+            && !self.exclude.iter().any(|d| d == name)
+    }
+}
+
+/// `/proc/diskstats` 中一个设备的累计计数器
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DiskIoCounters {
+    pub reads: u64,
+    pub sectors_read: u64,
+    pub read_ms: u64,
+    pub writes: u64,
+    pub sectors_written: u64,
+    pub write_ms: u64,
+    /// 当前正在进行的 I/O 数，不是累计值
+    pub in_flight: u64,
+    /// 设备忙碌的累计毫秒数
+    pub io_ms: u64,
+    /// 按队列长度加权的累计毫秒数
+    pub weighted_ms: u64,
+}
+
+impl DiskIoCounters {
+    /// `major minor name reads merged sectors ms writes merged sectors ms in_flight io_ms weighted_ms ...`
+    fn parse(fields: &[&str]) -> Option<Self> {
+        let field = |idx: usize| -> Option<u64> { fields.get(idx)?.parse().ok() };
+        Some(Self {
+            reads: field(3)?,
+            sectors_read: field(5)?,
+            read_ms: field(6)?,
+            writes: field(7)?,
+            sectors_written: field(9)?,
+            write_ms: field(10)?,
+            in_flight: field(11)?,
+            io_ms: field(12)?,
+            weighted_ms: field(13)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DiskIoDevice {
+    pub name: String,
+    pub counters: DiskIoCounters,
+    pub reads_per_sec: f64,
+    pub writes_per_sec: f64,
+    pub read_bytes_per_sec: f64,
+    pub write_bytes_per_sec: f64,
+    /// 每次 I/O 的平均耗时（毫秒），含排队时间
+    pub await_ms: f64,
+    /// 设备忙碌时间占比
+    pub util_percent: f64,
+    /// 平均队列长度
+    pub queue_depth: f64,
+}
+
+/// /proc/diskstats 的扇区固定为 512 字节，与设备实际扇区大小无关
+const SECTOR_BYTES: f64 = 512.0;
+
+impl DiskIoDevice {
+    /// 由两次计数器在 `dt` 秒内的变化计算各项指标；计数器回退时返回 false
+    fn update(&mut self, prev: &DiskIoCounters, dt: f64) -> bool {
+        let cur = &self.counters;
+        let (
+            Some(reads),
+            Some(writes),
+            Some(read_sectors),
+            Some(write_sectors),
+            Some(read_ms),
+            Some(write_ms),
+            Some(io_ms),
+            Some(weighted_ms),
+        ) = (
+            rate(cur.reads, prev.reads, dt),
+            rate(cur.writes, prev.writes, dt),
+            rate(cur.sectors_read, prev.sectors_read, dt),
+            rate(cur.sectors_written, prev.sectors_written, dt),
+            rate(cur.read_ms, prev.read_ms, dt),
+            rate(cur.write_ms, prev.write_ms, dt),
+            rate(cur.io_ms, prev.io_ms, dt),
+            rate(cur.weighted_ms, prev.weighted_ms, dt),
+        )
+        else {
+            return false;
+        };
+        self.reads_per_sec = reads;
+        self.writes_per_sec = writes;
+        self.read_bytes_per_sec = read_sectors * SECTOR_BYTES;
+        self.write_bytes_per_sec = write_sectors * SECTOR_BYTES;
+        self.await_ms = if reads + writes > 0.0 {
+            (read_ms + write_ms) / (reads + writes)
+        } else {
+            0.0
+        };
+        self.util_percent = (io_ms / 10.0).min(100.0);
+        self.queue_depth = weighted_ms / 1000.0;
+        true
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DiskIoInfo {
+    pub devices: Vec<DiskIoDevice>,
+    /// 采样时远端的时间戳（秒），见 [`RemoteOs::clock_cmd`]
+    pub uptime: f64,
+}
+
+impl Monitorable for DiskIoInfo {
+    fn supported(&self, os: RemoteOs) -> bool {
+        os == RemoteOs::Linux
+    }
+
+    fn exec_cmd(&self, config: &MonitorExecConfig) -> String {
+        // /sys/block 只列出整块设备，用来区分分区
+        let clock = config.os.clock_cmd();
+        format!("{clock}; cat /proc/diskstats; echo '---'; ls /sys/block")
+    }
+
+    fn parse_from_str(&mut self, s: &str, config: &MonitorExecConfig) -> Result<()> {
+        let (stats, block) = s.split_once("\n---\n").unwrap_or((s, ""));
+        let uptime = parse_uptime(stats)?;
+        let whole_disks: Vec<&str> = block.split_whitespace().collect();
+        let opts = &config.opts.diskio;
+
+        self.devices = stats
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let name = *fields.get(2)?;
+                if !opts.wants(name, whole_disks.contains(&name)) {
+                    return None;
+                }
+                Some(DiskIoDevice {
+                    name: name.to_string(),
+                    counters: DiskIoCounters::parse(&fields)?,
+                    ..DiskIoDevice::default()
+                })
+            })
+            .collect();
+        // 首次采样没有参照，先给出开机以来的平均值
+        for device in &mut self.devices {
+            device.update(&DiskIoCounters::default(), uptime.max(1.0));
+        }
+        self.uptime = uptime;
+        Ok(())
+    }
+
+    fn apply_previous(&mut self, prev: &Self) {
+        let Some(dt) = elapsed(self.uptime, prev.uptime) else {
+            return;
+        };
+        for device in &mut self.devices {
+            if let Some(old) = prev.devices.iter().find(|d| d.name == device.name) {
+                device.update(&old.counters, dt);
+            }
+        }
+    }
+
+    fn common_display(&self) -> String {
+        let mut s = format!(
+            "{:<12} {:>8} {:>8} {:>10} {:>10} {:>8} {:>7} {:>6}\n",
+            "Device", "r/s", "w/s", "rKB/s", "wKB/s", "await", "aqu-sz", "%util"
+        );
+        for d in &self.devices {
+            s.push_str(&format!(
+                "{:<12} {:>8.1} {:>8.1} {:>10.1} {:>10.1} {:>6.2}ms {:>7.2} {:>5.1}%\n",
+                d.name,
+                d.reads_per_sec,
+                d.writes_per_sec,
+                d.read_bytes_per_sec / 1024.0,
+                d.write_bytes_per_sec / 1024.0,
+                d.await_ms,
+                d.queue_depth,
+                d.util_percent
+            ));
+        }
+        s
+    }
+}
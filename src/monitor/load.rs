@@ -0,0 +1,198 @@
+use crate::Monitorable;
+use crate::model::Alert;
+use crate::monitor::{MonitorExecConfig, RemoteOs};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LoadInfo {
+    /// 1/5/15 分钟平均负载
+    pub load: [f64; 3],
+    pub cores: u32,
+    /// 正在运行的任务数，仅 Linux
+    pub running: Option<u32>,
+    /// 任务总数，仅 Linux
+    pub tasks: Option<u32>,
+    /// 最近分配的 PID，仅 Linux
+    pub last_pid: Option<u32>,
+    /// 开机时长（秒）
+    pub uptime: f64,
+    pub boot_time: Option<DateTime<Utc>>,
+    /// 每次开机唯一的标识：Linux 取 `boot_id`，macOS 取 `kern.bootsessionuuid`，FreeBSD 为空
+    pub boot_id: String,
+}
+
+/// 开机时长允许的回退量（秒）。BSD 的开机时长由 `kern.boottime` 推算，
+/// 系统校时后 `kern.boottime` 会随之漂移
+const UPTIME_DRIFT_SECS: f64 = 60.0;
+
+impl LoadInfo {
+    /// 按核数归一化后的平均负载，1.0 表示所有核满载
+    pub fn per_core(&self) -> [f64; 3] {
+        let cores = self.cores.max(1) as f64;
+        self.load.map(|l| l / cores)
+    }
+
+    /// `cat /proc/loadavg /proc/uptime /proc/sys/kernel/random/boot_id`、btime 与核数，各占一行
+    fn parse_linux(s: &str) -> Result<Self> {
+        let mut lines = s.lines();
+        let loadavg: Vec<&str> = lines.next().unwrap_or("").split_whitespace().collect();
+        if loadavg.len() < 5 {
+            anyhow::bail!("unexpected /proc/loadavg format");
+        }
+        let (running, tasks) = loadavg[3]
+            .split_once('/')
+            .ok_or_else(|| anyhow::anyhow!("unexpected /proc/loadavg format"))?;
+        let uptime = lines
+            .next()
+            .and_then(|l| l.split_whitespace().next())
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| anyhow::anyhow!("missing /proc/uptime"))?;
+        let boot_id = lines.next().unwrap_or("").trim().to_string();
+        let boot_time = lines
+            .next()
+            .and_then(|l| l.strip_prefix("btime"))
+            .and_then(|v| v.trim().parse().ok())
+            .and_then(|secs| DateTime::from_timestamp(secs, 0));
+        let cores = lines
+            .next()
+            .and_then(|l| l.trim().parse().ok())
+            .unwrap_or(1);
+        Ok(Self {
+            load: parse_load(&loadavg[..3])?,
+            cores,
+            running: running.parse().ok(),
+            tasks: tasks.parse().ok(),
+            last_pid: loadavg[4].parse().ok(),
+            uptime,
+            boot_time,
+            boot_id,
+        })
+    }
+
+    /// `sysctl -n vm.loadavg kern.boottime hw.ncpu` 与 `date +%s`，macOS 另有 `kern.bootsessionuuid`：
+    ///
+    /// ```text
+    /// { 1.52 1.60 1.71 }
+    /// { sec = 1700000000, usec = 123456 } Tue Nov 14 22:13:20 2023
+    /// 8
+    /// 1700003600
+    /// 5B0C3F2E-8D4A-4C1B-9E2F-6A7D8C9B0A1F
+    /// ```
+    fn parse_bsd(s: &str) -> Result<Self> {
+        let lines: Vec<&str> = s.lines().collect();
+        if lines.len() < 4 {
+            anyhow::bail!("unexpected sysctl output");
+        }
+        let load: Vec<&str> = lines[0]
+            .split_whitespace()
+            .filter(|v| *v != "{" && *v != "}")
+            .collect();
+        let boot_secs: i64 = lines[1]
+            .split("sec = ")
+            .nth(1)
+            .and_then(|v| v.split(',').next())
+            .and_then(|v| v.trim().parse().ok())
+            .ok_or_else(|| anyhow::anyhow!("unexpected kern.boottime format"))?;
+        let now: i64 = lines[3]
+            .trim()
+            .parse()
+            .map_err(|_| anyhow::anyhow!("missing remote time"))?;
+        Ok(Self {
+            load: parse_load(&load)?,
+            cores: lines[2].trim().parse().unwrap_or(1),
+            running: None,
+            tasks: None,
+            last_pid: None,
+            uptime: (now - boot_secs).max(0) as f64,
+            boot_time: DateTime::from_timestamp(boot_secs, 0),
+            // kern.boottime 会随校时漂移，不能作为开机标识
+            boot_id: lines.get(4).map_or("", |l| l.trim()).to_string(),
+        })
+    }
+}
+
+fn parse_load(values: &[&str]) -> Result<[f64; 3]> {
+    if values.len() < 3 {
+        anyhow::bail!("missing load averages");
+    }
+    let mut load = [0.0; 3];
+    for (slot, value) in load.iter_mut().zip(values) {
+        *slot = value
+            .parse()
+            .map_err(|_| anyhow::anyhow!("invalid load average {value:?}"))?;
+    }
+    Ok(load)
+}
+
+/// 形如 `3d 4h 12m` 的时长
+pub fn format_duration(secs: f64) -> String {
+    let secs = secs.max(0.0) as u64;
+    let (days, hours, mins) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+    match (days, hours) {
+        (0, 0) => format!("{mins}m {}s", secs % 60),
+        (0, _) => format!("{hours}h {mins}m"),
+        _ => format!("{days}d {hours}h {mins}m"),
+    }
+}
+
+impl Monitorable for LoadInfo {
+    fn exec_cmd(&self, config: &MonitorExecConfig) -> String {
+        match config.os {
+            RemoteOs::Linux => "cat /proc/loadavg /proc/uptime /proc/sys/kernel/random/boot_id; \
+                grep '^btime' /proc/stat; grep -c ^processor /proc/cpuinfo"
+                .to_string(),
+            RemoteOs::MacOs => {
+                "sysctl -n vm.loadavg kern.boottime hw.ncpu; date +%s; sysctl -n kern.bootsessionuuid"
+                    .to_string()
+            }
+            RemoteOs::FreeBsd => "sysctl -n vm.loadavg kern.boottime hw.ncpu; date +%s".to_string(),
+        }
+    }
+
+    fn parse_from_str(&mut self, s: &str, config: &MonitorExecConfig) -> Result<()> {
+        *self = match config.os {
+            RemoteOs::Linux => LoadInfo::parse_linux(s)?,
+            RemoteOs::MacOs | RemoteOs::FreeBsd => LoadInfo::parse_bsd(s)?,
+        };
+        Ok(())
+    }
+
+    fn alerts(&self, prev: &Self) -> Vec<Alert> {
+        // 开机时长明显回退说明已重启；若断线期间重启后又运行得比上次采样更久，只能靠开机标识发现
+        let boot_changed =
+            !self.boot_id.is_empty() && !prev.boot_id.is_empty() && self.boot_id != prev.boot_id;
+        if self.uptime + UPTIME_DRIFT_SECS < prev.uptime || boot_changed {
+            vec![Alert::Reboot {
+                uptime: self.uptime,
+            }]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn common_display(&self) -> String {
+        let [l1, l5, l15] = self.load;
+        let [p1, p5, p15] = self.per_core();
+        let mut s = format!(
+            "Load Average: {l1:.2} {l5:.2} {l15:.2} (per core: {p1:.2} {p5:.2} {p15:.2}, {} cores)\n",
+            self.cores
+        );
+        if let (Some(running), Some(tasks)) = (self.running, self.tasks) {
+            s.push_str(&format!("Tasks: {running} running / {tasks} total"));
+            if let Some(pid) = self.last_pid {
+                s.push_str(&format!(", last PID {pid}"));
+            }
+            s.push('\n');
+        }
+        s.push_str(&format!("Uptime: {}", format_duration(self.uptime)));
+        if let Some(boot) = self.boot_time {
+            s.push_str(&format!(
+                ", booted {}",
+                boot.format("%Y-%m-%d %H:%M:%S UTC")
+            ));
+        }
+        s.push('\n');
+        s
+    }
+}
@@ -1,4 +1,5 @@
 use crate::Monitorable;
+use crate::monitor::{MonitorExecConfig, RemoteOs, parse_key_values};
 use std::collections::HashMap;
 
 /// Parsed subset of /proc/meminfo. Fields are in kB.
@@ -61,44 +62,11 @@ impl MemInfo {
             _ => None,
         }
     }
-}
-
-impl Monitorable for MemInfo {
-    fn exec_cmd(&self) -> &'static str {
-        "cat /proc/meminfo"
-    }
-
-    /// Parse the full text of /proc/meminfo into a MemInfo.
-    fn parse_from_str(&mut self, s: &str) -> anyhow::Result<()> {
-        let mut map: HashMap<String, u64> = HashMap::new();
-
-        for line in s.lines() {
-            let line = line.trim();
-            if line.is_empty() {
-                continue;
-            }
-            // Expect lines like: "MemTotal:       8006048 kB"
-            if let Some(pos) = line.find(':') {
-                let key = line[..pos].trim().to_string();
-                let rest = line[pos + 1..].trim();
-                // Extract leading number
-                let mut num_str = String::new();
-                for ch in rest.chars() {
-                    if ch.is_ascii_digit() {
-                        num_str.push(ch);
-                    } else if !num_str.is_empty() {
-                        break;
-                    }
-                }
-                if num_str.is_empty() {
-                    continue;
-                }
-                if let Ok(val) = num_str.parse::<u64>() {
-                    map.insert(key, val);
-                }
-            }
-        }
 
+    /// Parse the full text of /proc/meminfo.
+    fn parse_linux(s: &str) -> anyhow::Result<Self> {
+        // Lines look like: "MemTotal:       8006048 kB"
+        let map = parse_key_values(s);
         let mem_total_kb = *map
             .get("MemTotal")
             .ok_or_else(|| anyhow::anyhow!("MemTotal missing"))?;
@@ -106,7 +74,7 @@ impl Monitorable for MemInfo {
             .get("MemFree")
             .ok_or_else(|| anyhow::anyhow!("MemFree missing"))?;
 
-        *self = MemInfo {
+        Ok(MemInfo {
             mem_total_kb,
             mem_free_kb,
             mem_available_kb: map.get("MemAvailable").copied(),
@@ -114,7 +82,119 @@ impl Monitorable for MemInfo {
             cached_kb: map.get("Cached").copied(),
             swap_total_kb: map.get("SwapTotal").copied(),
             swap_free_kb: map.get("SwapFree").copied(),
-            other: map.into_iter().collect(),
+            other: map,
+        })
+    }
+
+    /// Parse `sysctl hw.memsize hw.pagesize`, `vm_stat` and `sysctl vm.swapusage`.
+    ///
+    /// Available memory follows Activity Monitor: free + inactive + speculative pages.
+    fn parse_macos(s: &str) -> anyhow::Result<Self> {
+        let map = parse_key_values(s);
+        let get = |key: &str| {
+            map.get(key)
+                .copied()
+                .ok_or_else(|| anyhow::anyhow!("{key} missing"))
+        };
+        let page_kb = get("hw.pagesize")? / 1024;
+        let pages = |key: &str| map.get(key).copied().unwrap_or(0) * page_kb;
+        let free = get("Pages free")? * page_kb;
+
+        let (swap_total_kb, swap_free_kb) = match s.lines().find(|l| l.starts_with("vm.swapusage"))
+        {
+            Some(line) => {
+                let total = swap_field_kb(line, "total");
+                let free = swap_field_kb(line, "free");
+                (total, free)
+            }
+            None => (None, None),
+        };
+
+        Ok(MemInfo {
+            mem_total_kb: get("hw.memsize")? / 1024,
+            mem_free_kb: free,
+            mem_available_kb: Some(free + pages("Pages inactive") + pages("Pages speculative")),
+            buffers_kb: None,
+            cached_kb: Some(pages("File-backed pages")),
+            swap_total_kb,
+            swap_free_kb,
+            other: map,
+        })
+    }
+
+    /// Parse `sysctl hw.physmem hw.pagesize vm.stats.vm.*` followed by `swapinfo -k`.
+    fn parse_freebsd(s: &str) -> anyhow::Result<Self> {
+        let map = parse_key_values(s);
+        let get = |key: &str| {
+            map.get(key)
+                .copied()
+                .ok_or_else(|| anyhow::anyhow!("{key} missing"))
+        };
+        let page_kb = get("hw.pagesize")? / 1024;
+        let pages = |key: &str| map.get(key).copied().unwrap_or(0) * page_kb;
+        let free = get("vm.stats.vm.v_free_count")? * page_kb;
+
+        // swapinfo: "Device 1K-blocks Used Avail Capacity", one line per device
+        let mut swap: Option<(u64, u64)> = None;
+        for line in s.lines().filter(|l| l.starts_with("/dev/")) {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            if let (Some(Ok(total)), Some(Ok(avail))) = (
+                cols.get(1).map(|v| v.parse::<u64>()),
+                cols.get(3).map(|v| v.parse::<u64>()),
+            ) {
+                let (t, a) = swap.get_or_insert((0, 0));
+                *t += total;
+                *a += avail;
+            }
+        }
+
+        Ok(MemInfo {
+            mem_total_kb: get("hw.physmem")? / 1024,
+            mem_free_kb: free,
+            mem_available_kb: Some(
+                free + pages("vm.stats.vm.v_inactive_count") + pages("vm.stats.vm.v_cache_count"),
+            ),
+            buffers_kb: None,
+            cached_kb: None,
+            swap_total_kb: swap.map(|(t, _)| t),
+            swap_free_kb: swap.map(|(_, a)| a),
+            other: map,
+        })
+    }
+}
+
+/// Read one `name = 123.45M` field of `vm.swapusage` as kB.
+fn swap_field_kb(line: &str, name: &str) -> Option<u64> {
+    let rest = line.split(&format!("{name} = ")).nth(1)?;
+    let value = rest.split_whitespace().next()?;
+    let (num, unit) = value.split_at(value.len().checked_sub(1)?);
+    let num: f64 = num.parse().ok()?;
+    let kb = match unit {
+        "K" => num,
+        "M" => num * 1024.0,
+        "G" => num * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some(kb as u64)
+}
+
+impl Monitorable for MemInfo {
+    fn exec_cmd(&self, config: &MonitorExecConfig) -> String {
+        match config.os {
+            RemoteOs::Linux => "cat /proc/meminfo",
+            RemoteOs::MacOs => "sysctl hw.memsize hw.pagesize vm.swapusage; vm_stat",
+            RemoteOs::FreeBsd => {
+                "sysctl hw.physmem hw.pagesize vm.stats.vm.v_free_count vm.stats.vm.v_inactive_count vm.stats.vm.v_cache_count; swapinfo -k"
+            }
+        }
+        .to_string()
+    }
+
+    fn parse_from_str(&mut self, s: &str, config: &MonitorExecConfig) -> anyhow::Result<()> {
+        *self = match config.os {
+            RemoteOs::Linux => MemInfo::parse_linux(s)?,
+            RemoteOs::MacOs => MemInfo::parse_macos(s)?,
+            RemoteOs::FreeBsd => MemInfo::parse_freebsd(s)?,
         };
         Ok(())
     }
@@ -135,7 +215,7 @@ impl Monitorable for MemInfo {
         if let Some(swap_pct) = self.swap_used_percent() {
             out.push_str(&format!(", Swap Used: {:.2} %", swap_pct));
         }
-        out.push_str("\n");
+        out.push('\n');
         out
     }
 }
@@ -6,11 +6,249 @@ pub mod disk;
 pub use disk::DiskInfo;
 pub mod net;
 pub use net::NetInfo;
+pub mod diskio;
+pub use diskio::DiskIoInfo;
+pub mod load;
+pub use load::LoadInfo;
+pub mod process;
+pub use process::ProcessWatchInfo;
+pub mod sockets;
+pub use sockets::SocketsInfo;
+pub mod sensors;
+pub use sensors::SensorsInfo;
+pub mod systemd;
+pub use systemd::SystemdInfo;
+pub mod pressure;
+pub use pressure::PressureInfo;
+pub mod custom;
+pub use custom::{CustomInfo, CustomMonitor};
+pub mod plugin;
+pub use plugin::{Plugin, PluginConfig, PluginInfo};
+
+use crate::config::ServerConfig;
+use crate::model::Alert;
+use serde::Deserialize;
+
+/// 各监控项的可选参数，对应 `[servers.monitor_opts.<kind>]`
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct MonitorOpts {
+    #[serde(default)]
+    pub cpu: cpu::CpuOpts,
+    #[serde(default)]
+    pub disk: disk::DiskOpts,
+    #[serde(default)]
+    pub net: net::NetOpts,
+    #[serde(default)]
+    pub diskio: diskio::DiskIoOpts,
+    #[serde(default)]
+    pub process: process::ProcessOpts,
+    #[serde(default)]
+    pub systemd: systemd::SystemdOpts,
+    /// 本服务器引用的 `[[custom_monitors]]`，加载配置时填充
+    #[serde(skip)]
+    pub custom: Vec<CustomMonitor>,
+    /// 本服务器引用的插件，加载配置时填充
+    #[serde(skip)]
+    pub plugins: Vec<Plugin>,
+}
+
+impl MonitorOpts {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.disk.validate()?;
+        self.process.validate()
+    }
+
+    pub fn custom(&self, name: &str) -> Option<&CustomMonitor> {
+        self.custom.iter().find(|m| m.name == name)
+    }
+
+    pub fn plugin(&self, name: &str) -> Option<&Plugin> {
+        self.plugins.iter().find(|p| p.name == name)
+    }
+}
+
+/// 远端操作系统，决定各监控项使用的命令与解析方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RemoteOs {
+    #[default]
+    Linux,
+    MacOs,
+    FreeBsd,
+}
+
+impl RemoteOs {
+    pub fn from_uname(output: &str) -> anyhow::Result<Self> {
+        match output.trim() {
+            "Linux" => Ok(RemoteOs::Linux),
+            "Darwin" => Ok(RemoteOs::MacOs),
+            "FreeBSD" => Ok(RemoteOs::FreeBsd),
+            other => anyhow::bail!("unsupported remote OS: {other:?}"),
+        }
+    }
+
+    /// 输出远端时间戳（秒）的命令，用于计算两次采样的间隔。
+    ///
+    /// Linux 读取 `/proc/uptime`；BSD 没有高精度的 `date`，优先用系统自带的 perl。
+    pub fn clock_cmd(self) -> &'static str {
+        match self {
+            RemoteOs::Linux => "cat /proc/uptime",
+            RemoteOs::MacOs | RemoteOs::FreeBsd => {
+                "perl -MTime::HiRes=time -e 'printf \"%.3f\\n\", time' 2>/dev/null || date +%s"
+            }
+        }
+    }
+}
+
+/// 远端 Linux 上可用的工具。BusyBox 的 `ps` 不支持 `--sort`、`df` 不支持 `-x`，
+/// 缺失时改为直接读取 `/proc`。
+///
+/// 同时记录换算 `/proc/<pid>/stat` 所需的时钟频率与页大小，它们因架构和内核配置而异。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToolCaps {
+    pub gnu_ps: bool,
+    pub gnu_df: bool,
+    /// `getconf CLK_TCK`：每秒的 jiffies 数
+    pub clk_tck: u64,
+    /// `getconf PAGESIZE`，单位 KiB
+    pub page_kb: u64,
+}
+
+impl Default for ToolCaps {
+    fn default() -> Self {
+        Self {
+            gnu_ps: true,
+            gnu_df: true,
+            clk_tck: 100,
+            page_kb: 4,
+        }
+    }
+}
+
+/// 每次建立连接后执行一次：识别远端系统，探测 GNU `ps`/`df` 的选项是否可用，
+/// 并读取页大小与时钟频率
+pub const PROBE_CMD: &str = "uname -s; \
+ps -eo pid,%cpu --sort=-%cpu >/dev/null 2>&1 && echo gnu-ps; \
+df -P -x devtmpfs / >/dev/null 2>&1 && echo gnu-df; \
+echo sysconf $(getconf PAGESIZE 2>/dev/null) $(getconf CLK_TCK 2>/dev/null); true";
+
+/// `sysconf <PAGESIZE> <CLK_TCK>`，返回 (KiB, Hz)；没有 getconf（如 BusyBox）时为 None
+fn parse_sysconf(line: &str) -> Option<(u64, u64)> {
+    let mut values = line
+        .strip_prefix("sysconf")?
+        .split_whitespace()
+        .map(|v| v.parse::<u64>().ok());
+    let page = values.next()??;
+    let clk_tck = values.next()??;
+    (page >= 1024 && clk_tck > 0).then_some((page / 1024, clk_tck))
+}
+
+/// 解析 [`PROBE_CMD`] 的输出
+pub fn parse_probe(output: &str) -> anyhow::Result<(RemoteOs, ToolCaps)> {
+    let mut lines = output.lines();
+    let os = RemoteOs::from_uname(lines.next().unwrap_or(""))?;
+    let found: Vec<&str> = lines.map(str::trim).collect();
+    let caps = match os {
+        RemoteOs::Linux => {
+            let defaults = ToolCaps::default();
+            let (page_kb, clk_tck) = found
+                .iter()
+                .find_map(|line| parse_sysconf(line))
+                .unwrap_or((defaults.page_kb, defaults.clk_tck));
+            ToolCaps {
+                gnu_ps: found.contains(&"gnu-ps"),
+                gnu_df: found.contains(&"gnu-df"),
+                clk_tck,
+                page_kb,
+            }
+        }
+        // BSD 使用各自的命令集，不依赖 GNU 选项
+        RemoteOs::MacOs | RemoteOs::FreeBsd => ToolCaps::default(),
+    };
+    Ok((os, caps))
+}
+
+/// 监控项构造命令与解析输出时使用的运行时配置
+#[derive(Debug, Clone, Default)]
+pub struct MonitorExecConfig {
+    pub opts: MonitorOpts,
+    /// 当前连接上识别出的远端系统
+    pub os: RemoteOs,
+    /// 当前连接上探测到的工具能力
+    pub caps: ToolCaps,
+}
+
+impl MonitorExecConfig {
+    pub fn new(server: &ServerConfig) -> Self {
+        Self {
+            opts: server.monitor_opts.clone(),
+            os: RemoteOs::default(),
+            caps: ToolCaps::default(),
+        }
+    }
+}
 
 pub trait Monitorable: Default {
-    fn exec_cmd(&self) -> &'static str;
+    /// 该监控项能否在 `os` 上采集；不支持的监控项由 agent 跳过，不会下发命令
+    fn supported(&self, _os: RemoteOs) -> bool {
+        true
+    }
+
+    /// 按运行时配置生成要在远端执行的命令
+    fn exec_cmd(&self, config: &MonitorExecConfig) -> String;
+
+    fn parse_from_str(&mut self, s: &str, config: &MonitorExecConfig) -> anyhow::Result<()>;
 
-    fn parse_from_str(&mut self, s: &str) -> anyhow::Result<()>;
+    /// 用同一主机上一次的采样计算差值类指标（使用率、速率），无状态的监控项无需实现
+    fn apply_previous(&mut self, _prev: &Self) {}
+
+    /// 与上一次采样比较得出的事件，如重启
+    fn alerts(&self, _prev: &Self) -> Vec<Alert> {
+        Vec::new()
+    }
 
     fn common_display(&self) -> String;
 }
+
+/// 读取输出首行 [`RemoteOs::clock_cmd`] 的结果，作为远端采样时间
+pub(crate) fn parse_uptime(s: &str) -> anyhow::Result<f64> {
+    s.lines()
+        .next()
+        .and_then(|line| line.split_whitespace().next())
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| anyhow::anyhow!("missing remote timestamp"))
+}
+
+/// 相邻两次采样之间的秒数；远端时间戳没有前进（重启或时钟回拨）时返回 None
+pub(crate) fn elapsed(now: f64, prev: f64) -> Option<f64> {
+    let dt = now - prev;
+    (dt > 0.0).then_some(dt)
+}
+
+/// 累计计数器在 `dt` 秒内的平均速率；计数器变小（重置或回绕）时返回 None
+pub(crate) fn rate(cur: u64, prev: u64, dt: f64) -> Option<f64> {
+    (cur >= prev).then(|| (cur - prev) as f64 / dt)
+}
+
+/// 用单引号包裹，使任意字符串可以安全地拼进远端命令
+pub(crate) fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// 解析 `key: value` 形式的行（/proc/meminfo、sysctl、vm_stat），取冒号后的首个整数
+pub(crate) fn parse_key_values(s: &str) -> std::collections::HashMap<String, u64> {
+    let mut map = std::collections::HashMap::new();
+    for line in s.lines() {
+        let Some((key, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let digits: String = rest
+            .trim_start()
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        if let Ok(val) = digits.parse::<u64>() {
+            map.insert(key.trim().to_string(), val);
+        }
+    }
+    map
+}
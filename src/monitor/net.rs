@@ -1,5 +1,25 @@
 use crate::Monitorable;
+use crate::monitor::{MonitorExecConfig, RemoteOs, elapsed, parse_uptime, rate};
 use anyhow::Result;
+use serde::Deserialize;
+
+/// `[servers.monitor_opts.net]`
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct NetOpts {
+    /// 只显示这些接口，为空时显示全部
+    #[serde(default)]
+    pub interfaces: Vec<String>,
+    /// 不显示的接口，如 `["lo"]`
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl NetOpts {
+    fn wants(&self, iface: &str) -> bool {
+        (self.interfaces.is_empty() || self.interfaces.iter().any(|i| i == iface))
+            && !self.exclude.iter().any(|i| i == iface)
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct NetInterface {
@@ -13,6 +33,8 @@ pub struct NetInterface {
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct NetInfo {
     pub interfaces: Vec<NetInterface>,
+    /// 采样时远端的时间戳（秒），见 [`RemoteOs::clock_cmd`]
+    pub uptime: f64,
 }
 
 impl NetInfo {
@@ -22,35 +44,33 @@ impl NetInfo {
             if let Some((iface, rest)) = line.split_once(':') {
                 let iface = iface.trim().to_string();
                 let fields: Vec<&str> = rest.split_whitespace().collect();
-                if fields.len() >= 10 {
-                    if let (Ok(rx_bytes), Ok(tx_bytes)) =
+                if fields.len() >= 10
+                    && let (Ok(rx_bytes), Ok(tx_bytes)) =
                         (fields[0].parse::<u64>(), fields[8].parse::<u64>())
-                    {
-                        result.push((iface, rx_bytes, tx_bytes));
-                    }
+                {
+                    result.push((iface, rx_bytes, tx_bytes));
                 }
             }
         }
         result
     }
 
-    fn diff(
-        first: &[(String, u64, u64)],
-        second: &[(String, u64, u64)],
-        dt: f64,
-    ) -> Vec<NetInterface> {
+    /// 解析 BSD `netstat -ibn`，只取 `<Link#N>` 行，它携带接口的累计字节数。
+    ///
+    /// Address 列可能为空，FreeBSD 还多一列 Idrop，因此从行尾定位：
+    /// `... Ibytes Opkts Oerrs Obytes Coll`
+    fn parse_netstat(content: &str) -> Vec<(String, u64, u64)> {
         let mut result = Vec::new();
-        for (iface, rx1, tx1) in first {
-            if let Some((_, rx2, tx2)) = second.iter().find(|(n, _, _)| n == iface) {
-                let rx_rate = (*rx2 as f64 - *rx1 as f64) / dt;
-                let tx_rate = (*tx2 as f64 - *tx1 as f64) / dt;
-                result.push(NetInterface {
-                    name: iface.clone(),
-                    rx_bytes: *rx2,
-                    tx_bytes: *tx2,
-                    rx_rate,
-                    tx_rate,
-                });
+        for line in content.lines() {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            if cols.len() < 8 || !cols[2].starts_with("<Link") {
+                continue;
+            }
+            let n = cols.len();
+            if let (Ok(rx_bytes), Ok(tx_bytes)) = (cols[n - 5].parse(), cols[n - 2].parse()) {
+                // 已关闭的接口名带有 '*' 后缀
+                let name = cols[0].trim_end_matches('*').to_string();
+                result.push((name, rx_bytes, tx_bytes));
             }
         }
         result
@@ -58,27 +78,67 @@ impl NetInfo {
 }
 
 impl Monitorable for NetInfo {
-    fn exec_cmd(&self) -> &'static str {
-        // 两次采样 + 分隔符
-        "cat /proc/net/dev; sleep 0.2; cat /proc/net/dev"
-    }
-
-    fn parse_from_str(&mut self, s: &str) -> Result<()> {
-        let parts: Vec<&str> = s.split("Inter-|").collect();
-        if parts.len() < 3 {
-            anyhow::bail!("unexpected /proc/net/dev format");
+    fn exec_cmd(&self, config: &MonitorExecConfig) -> String {
+        // 单次读取计数器，速率由相邻两次采样求差得到
+        let clock = config.os.clock_cmd();
+        match config.os {
+            RemoteOs::Linux => format!("{clock}; cat /proc/net/dev"),
+            RemoteOs::MacOs | RemoteOs::FreeBsd => format!("{clock}; netstat -ibn"),
         }
-        // 恢复成两个 netdev 数据块
-        let first = format!("Inter-|{}", parts[1]);
-        let second = format!("Inter-|{}", parts[2]);
+    }
 
-        let first_list = Self::parse_netdev(&first);
-        let second_list = Self::parse_netdev(&second);
-        // 假设 sleep 0.2s
-        self.interfaces = Self::diff(&first_list, &second_list, 0.2);
+    fn parse_from_str(&mut self, s: &str, config: &MonitorExecConfig) -> Result<()> {
+        let uptime = parse_uptime(s)?;
+        let counters = match config.os {
+            RemoteOs::Linux => {
+                let netdev = s
+                    .find("Inter-|")
+                    .map(|idx| &s[idx..])
+                    .ok_or_else(|| anyhow::anyhow!("unexpected /proc/net/dev format"))?;
+                Self::parse_netdev(netdev)
+            }
+            RemoteOs::MacOs | RemoteOs::FreeBsd => Self::parse_netstat(s),
+        };
+        // 首次采样没有参照，先给出开机以来的平均速率；
+        // BSD 的时间戳是墙上时间而非开机时长，只能等下一次采样
+        let since_boot = match config.os {
+            RemoteOs::Linux => uptime.max(1.0),
+            RemoteOs::MacOs | RemoteOs::FreeBsd => f64::INFINITY,
+        };
+        self.interfaces = counters
+            .into_iter()
+            .filter(|(name, _, _)| config.opts.net.wants(name))
+            .map(|(name, rx_bytes, tx_bytes)| NetInterface {
+                name,
+                rx_bytes,
+                tx_bytes,
+                rx_rate: rx_bytes as f64 / since_boot,
+                tx_rate: tx_bytes as f64 / since_boot,
+            })
+            .collect();
+        self.uptime = uptime;
         Ok(())
     }
 
+    fn apply_previous(&mut self, prev: &Self) {
+        // 按远端真实间隔计算，而不是假设固定的采样周期
+        let Some(dt) = elapsed(self.uptime, prev.uptime) else {
+            return;
+        };
+        for iface in &mut self.interfaces {
+            // 新出现的接口或计数器回绕时保留平均值
+            if let Some(old) = prev.interfaces.iter().find(|i| i.name == iface.name)
+                && let (Some(rx), Some(tx)) = (
+                    rate(iface.rx_bytes, old.rx_bytes, dt),
+                    rate(iface.tx_bytes, old.tx_bytes, dt),
+                )
+            {
+                iface.rx_rate = rx;
+                iface.tx_rate = tx;
+            }
+        }
+    }
+
     fn common_display(&self) -> String {
         let mut s = String::from("Network Interfaces:\n");
         for i in &self.interfaces {
@@ -0,0 +1,287 @@
+//! 外部监控插件：本机上的可执行文件，无需重新编译 stalking 即可增加监控项。
+//!
+//! 加载配置时执行 `<path> describe`，插件在 stdout 输出自身的描述：
+//!
+//! ```json
+//! {"name": "redis", "label": "Redis", "command": "redis-cli -p {port} info stats",
+//!  "interval_ms": 5000, "options": {"port": 6379}}
+//! ```
+//!
+//! `command` 中的 `{option}` 会替换为经 shell 引号转义的选项值；`options` 是默认值，
+//! 可被 `[[plugins]]` 中的 `options` 覆盖。每次采样后在本机执行 `<path> parse`，
+//! 远端命令的原始 stdout 写入插件的 stdin，合并后的选项以 JSON 放在环境变量
+//! `STALKING_OPTIONS` 中，插件输出：
+//!
+//! ```json
+//! {"metrics": [{"key": "ops", "value": 1520, "unit": "/s", "label": "ops"}]}
+//! ```
+//!
+//! `unit` 与 `label` 可省略；以非零状态退出时 stderr 作为错误信息。两个子命令都须在
+//! `timeout_ms` 内结束，超时的插件进程会被杀掉。
+
+use crate::Monitorable;
+use crate::monitor::custom::{Metric, MetricValue, display_metrics};
+use crate::monitor::{MonitorExecConfig, shell_quote};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// `[[plugins]]`：要加载的插件
+///
+/// ```toml
+/// [[plugins]]
+/// path = "/usr/local/lib/stalking/redis"
+/// options = { port = 6380 }
+/// timeout_ms = 2000
+/// ```
+#[derive(Debug, Deserialize, Clone)]
+pub struct PluginConfig {
+    pub path: PathBuf,
+    /// 覆盖插件声明的默认选项
+    #[serde(default)]
+    pub options: toml::Table,
+    /// `describe` 与每次 `parse` 的时限
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_timeout_ms() -> u64 {
+    5000
+}
+
+/// `describe` 的输出
+#[derive(Debug, Deserialize)]
+struct Descriptor {
+    name: String,
+    command: String,
+    label: Option<String>,
+    interval_ms: Option<u64>,
+    #[serde(default)]
+    options: Map<String, Value>,
+}
+
+/// `parse` 的输出
+#[derive(Debug, Deserialize)]
+struct PluginOutput {
+    metrics: Vec<PluginMetric>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginMetric {
+    key: String,
+    value: Value,
+    #[serde(default)]
+    unit: String,
+    label: Option<String>,
+}
+
+/// 已加载的插件
+#[derive(Debug, Clone)]
+pub struct Plugin {
+    pub name: String,
+    pub path: PathBuf,
+    pub label: String,
+    /// 已替换选项的远端命令
+    pub command: String,
+    pub interval_ms: Option<u64>,
+    pub options: Map<String, Value>,
+    pub timeout: Duration,
+}
+
+impl Plugin {
+    /// 执行 `describe` 并合并配置中的选项
+    pub async fn load(config: &PluginConfig) -> Result<Self> {
+        let timeout = Duration::from_millis(config.timeout_ms);
+        let output = run(&config.path, "describe", "", &Map::new(), timeout).await?;
+        let desc: Descriptor = serde_json::from_slice(&output).with_context(|| {
+            format!(
+                "plugin {} printed an invalid description",
+                config.path.display()
+            )
+        })?;
+        if desc.name.is_empty()
+            || !desc
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_-".contains(c))
+        {
+            anyhow::bail!(
+                "Plugin {} has an invalid name {:?}",
+                config.path.display(),
+                desc.name
+            );
+        }
+
+        let mut options = desc.options;
+        for (key, value) in &config.options {
+            if !options.contains_key(key) {
+                anyhow::bail!("Plugin {} has no option {key:?}", desc.name);
+            }
+            options.insert(key.clone(), serde_json::to_value(value)?);
+        }
+        let command = substitute(&desc.command, &options);
+        if command.trim().is_empty() {
+            anyhow::bail!("Plugin {} declares an empty command", desc.name);
+        }
+
+        Ok(Self {
+            label: desc.label.unwrap_or_else(|| desc.name.clone()),
+            name: desc.name,
+            path: config.path.clone(),
+            command,
+            interval_ms: desc.interval_ms,
+            options,
+            timeout,
+        })
+    }
+
+    /// 把远端输出交给插件解析
+    pub async fn parse(&self, stdout: &str) -> Result<Vec<Metric>> {
+        let output = run(&self.path, "parse", stdout, &self.options, self.timeout).await?;
+        let output: PluginOutput = serde_json::from_slice(&output)
+            .with_context(|| format!("plugin {} printed invalid metrics", self.name))?;
+        output
+            .metrics
+            .into_iter()
+            .map(|m| {
+                let value = MetricValue::from_json(&m.value).ok_or_else(|| {
+                    anyhow::anyhow!("plugin {} metric {} is not a scalar", self.name, m.key)
+                })?;
+                Ok(Metric {
+                    label: m.label.unwrap_or_else(|| m.key.clone()),
+                    key: m.key,
+                    value,
+                    unit: m.unit,
+                })
+            })
+            .collect()
+    }
+}
+
+/// 把已声明选项的 `{option}` 替换为引号转义后的值，其余花括号（如 awk 脚本）原样保留
+fn substitute(command: &str, options: &Map<String, Value>) -> String {
+    let re = regex::Regex::new(r"\{([A-Za-z_][A-Za-z0-9_]*)\}").expect("valid regex");
+    re.replace_all(command, |caps: &regex::Captures| {
+        match options.get(&caps[1]) {
+            Some(Value::String(s)) => shell_quote(s),
+            Some(other) => shell_quote(&other.to_string()),
+            None => caps[0].to_string(),
+        }
+    })
+    .into_owned()
+}
+
+/// 在本机执行插件的一个子命令，返回 stdout；超过 `timeout` 时杀掉插件进程
+async fn run(
+    path: &Path,
+    action: &str,
+    stdin: &str,
+    options: &Map<String, Value>,
+    timeout: Duration,
+) -> Result<Vec<u8>> {
+    let mut child = Command::new(path)
+        .arg(action)
+        .env(
+            "STALKING_OPTIONS",
+            Value::Object(options.clone()).to_string(),
+        )
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("failed to run plugin {}", path.display()))?;
+    let mut pipe = child.stdin.take().expect("stdin is piped");
+    // 写 stdin 与读输出同时进行，避免输出较大时双方互相阻塞
+    let write = async move {
+        // 插件不读 stdin 就退出时写入会失败，这不算错误
+        let _ = pipe.write_all(stdin.as_bytes()).await;
+    };
+    // 超时后 future 被丢弃，kill_on_drop 随之杀掉子进程
+    let (_, output) = tokio::time::timeout(timeout, async {
+        tokio::join!(write, child.wait_with_output())
+    })
+    .await
+    .map_err(|_| {
+        anyhow::anyhow!(
+            "plugin {} {action} timed out after {}ms",
+            path.display(),
+            timeout.as_millis()
+        )
+    })?;
+    let output = output?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "plugin {} {action} failed ({}): {}",
+            path.display(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// 插件的一次采样
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PluginInfo {
+    pub name: String,
+    pub label: String,
+    pub metrics: Vec<Metric>,
+    /// 远端命令的输出，等待 [`PluginInfo::run_plugin`] 交给插件解析
+    pending: Option<String>,
+}
+
+impl PluginInfo {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            label: name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// 在本机运行插件解析远端输出。
+    ///
+    /// 插件可能很慢，agent 在释放并发许可之后才调用，而不是在 `parse_from_str` 中。
+    pub async fn run_plugin(&mut self, config: &MonitorExecConfig) -> Result<()> {
+        let Some(stdout) = self.pending.take() else {
+            return Ok(());
+        };
+        let plugin = config
+            .opts
+            .plugin(&self.name)
+            .ok_or_else(|| anyhow::anyhow!("plugin {} is not loaded", self.name))?;
+        self.metrics = plugin.parse(&stdout).await?;
+        Ok(())
+    }
+}
+
+impl Monitorable for PluginInfo {
+    fn exec_cmd(&self, config: &MonitorExecConfig) -> String {
+        config
+            .opts
+            .plugin(&self.name)
+            .map(|p| p.command.clone())
+            .unwrap_or_else(|| "false".to_string())
+    }
+
+    fn parse_from_str(&mut self, s: &str, config: &MonitorExecConfig) -> Result<()> {
+        let plugin = config
+            .opts
+            .plugin(&self.name)
+            .ok_or_else(|| anyhow::anyhow!("plugin {} is not loaded", self.name))?;
+        self.label = plugin.label.clone();
+        self.metrics.clear();
+        self.pending = Some(s.to_string());
+        Ok(())
+    }
+
+    fn common_display(&self) -> String {
+        display_metrics(&self.label, &self.metrics)
+    }
+}
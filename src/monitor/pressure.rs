@@ -0,0 +1,194 @@
+use crate::Monitorable;
+use crate::model::Alert;
+use crate::monitor::{MonitorExecConfig, RemoteOs, elapsed, parse_uptime, rate};
+use anyhow::Result;
+
+/// PSI 中一行 `some`/`full` 的数据：过去 10/60/300 秒内任务因资源不足而停顿的时间占比
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PressureStall {
+    pub avg10: f64,
+    pub avg60: f64,
+    pub avg300: f64,
+    /// 累计停顿时间（微秒）
+    pub total_us: u64,
+}
+
+impl PressureStall {
+    /// `some avg10=0.12 avg60=0.05 avg300=0.01 total=123456`
+    fn parse(fields: &str) -> Option<Self> {
+        let mut stall = Self::default();
+        for field in fields.split_whitespace() {
+            let (key, value) = field.split_once('=')?;
+            match key {
+                "avg10" => stall.avg10 = value.parse().ok()?,
+                "avg60" => stall.avg60 = value.parse().ok()?,
+                "avg300" => stall.avg300 = value.parse().ok()?,
+                "total" => stall.total_us = value.parse().ok()?,
+                _ => {}
+            }
+        }
+        Some(stall)
+    }
+}
+
+/// `/proc/pressure/<resource>` 的内容
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PressureResource {
+    /// 至少一个任务在等待
+    pub some: PressureStall,
+    /// 所有非空闲任务同时在等待；较老的内核没有 cpu 的 full 行
+    pub full: Option<PressureStall>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PressureInfo {
+    /// 内核未开启 PSI（4.20 以前或 `psi=0`）时为 None
+    pub cpu: Option<PressureResource>,
+    pub memory: Option<PressureResource>,
+    pub io: Option<PressureResource>,
+    /// `/proc/vmstat` 中的累计计数（页）
+    pub pswpin: u64,
+    pub pswpout: u64,
+    pub pgmajfault: u64,
+    /// OOM killer 累计杀死的进程数，4.13 以前的内核没有
+    pub oom_kill: Option<u64>,
+    /// 每秒换入、换出的页数
+    pub swap_in_rate: f64,
+    pub swap_out_rate: f64,
+    /// 每秒主缺页次数
+    pub major_fault_rate: f64,
+    /// 采样时远端的时间戳（秒），见 [`RemoteOs::clock_cmd`]
+    pub uptime: f64,
+}
+
+impl PressureInfo {
+    /// `grep -H` 的输出，形如 `/proc/pressure/memory:full avg10=0.00 ...`
+    fn parse_psi(&mut self, s: &str) {
+        for line in s.lines() {
+            let Some((path, rest)) = line.split_once(':') else {
+                continue;
+            };
+            let slot = match path.rsplit('/').next() {
+                Some("cpu") => &mut self.cpu,
+                Some("memory") => &mut self.memory,
+                Some("io") => &mut self.io,
+                _ => continue,
+            };
+            let Some((kind, fields)) = rest.split_once(' ') else {
+                continue;
+            };
+            let Some(stall) = PressureStall::parse(fields) else {
+                continue;
+            };
+            let resource = slot.get_or_insert_with(PressureResource::default);
+            match kind {
+                "some" => resource.some = stall,
+                "full" => resource.full = Some(stall),
+                _ => {}
+            }
+        }
+    }
+
+    /// 由计数器在 `dt` 秒内的变化计算速率；计数器回退时保留原值
+    fn update_rates(&mut self, prev: (u64, u64, u64), dt: f64) {
+        if let (Some(swap_in), Some(swap_out), Some(faults)) = (
+            rate(self.pswpin, prev.0, dt),
+            rate(self.pswpout, prev.1, dt),
+            rate(self.pgmajfault, prev.2, dt),
+        ) {
+            self.swap_in_rate = swap_in;
+            self.swap_out_rate = swap_out;
+            self.major_fault_rate = faults;
+        }
+    }
+}
+
+impl Monitorable for PressureInfo {
+    fn supported(&self, os: RemoteOs) -> bool {
+        os == RemoteOs::Linux
+    }
+
+    fn exec_cmd(&self, config: &MonitorExecConfig) -> String {
+        // 没有 PSI 的内核上 grep 失败，只剩 vmstat 部分
+        let clock = config.os.clock_cmd();
+        format!(
+            "{clock}; grep -H . /proc/pressure/cpu /proc/pressure/memory /proc/pressure/io 2>/dev/null; \
+             echo '---'; grep -E '^(pswpin|pswpout|pgmajfault|oom_kill) ' /proc/vmstat"
+        )
+    }
+
+    fn parse_from_str(&mut self, s: &str, _config: &MonitorExecConfig) -> Result<()> {
+        let (psi, vmstat) = s
+            .split_once("\n---\n")
+            .ok_or_else(|| anyhow::anyhow!("missing /proc/vmstat output"))?;
+        let uptime = parse_uptime(psi)?;
+        *self = Self::default();
+        self.parse_psi(psi);
+        for line in vmstat.lines() {
+            let Some((key, value)) = line.split_once(' ') else {
+                continue;
+            };
+            let Ok(value) = value.trim().parse() else {
+                continue;
+            };
+            match key {
+                "pswpin" => self.pswpin = value,
+                "pswpout" => self.pswpout = value,
+                "pgmajfault" => self.pgmajfault = value,
+                "oom_kill" => self.oom_kill = Some(value),
+                _ => {}
+            }
+        }
+        // 首次采样没有参照，先给出开机以来的平均值
+        self.update_rates((0, 0, 0), uptime.max(1.0));
+        self.uptime = uptime;
+        Ok(())
+    }
+
+    fn apply_previous(&mut self, prev: &Self) {
+        if let Some(dt) = elapsed(self.uptime, prev.uptime) {
+            self.update_rates((prev.pswpin, prev.pswpout, prev.pgmajfault), dt);
+        }
+    }
+
+    fn alerts(&self, prev: &Self) -> Vec<Alert> {
+        // 计数器变小说明远端重启过，重启由 Load 监控报告
+        match (self.oom_kill, prev.oom_kill) {
+            (Some(cur), Some(old)) if cur > old => vec![Alert::OomKill { kills: cur - old }],
+            _ => Vec::new(),
+        }
+    }
+
+    fn common_display(&self) -> String {
+        let mut s = String::new();
+        if self.cpu.is_none() && self.memory.is_none() && self.io.is_none() {
+            s.push_str("Pressure stall information not available on this kernel\n");
+        } else {
+            s.push_str(&format!(
+                "{:<8} {:>6} {:>6} {:>6} | {:>6} {:>6} {:>6}\n",
+                "PSI %", "some10", "60", "300", "full10", "60", "300"
+            ));
+            for (name, resource) in [("cpu", self.cpu), ("memory", self.memory), ("io", self.io)] {
+                let Some(r) = resource else {
+                    continue;
+                };
+                let full = match r.full {
+                    Some(f) => format!("{:>6.2} {:>6.2} {:>6.2}", f.avg10, f.avg60, f.avg300),
+                    None => format!("{:>6} {:>6} {:>6}", "-", "-", "-"),
+                };
+                s.push_str(&format!(
+                    "{name:<8} {:>6.2} {:>6.2} {:>6.2} | {full}\n",
+                    r.some.avg10, r.some.avg60, r.some.avg300
+                ));
+            }
+        }
+        s.push_str(&format!(
+            "Swap in: {:.1} pages/s, out: {:.1} pages/s, major faults: {:.1}/s\n",
+            self.swap_in_rate, self.swap_out_rate, self.major_fault_rate
+        ));
+        if let Some(kills) = self.oom_kill {
+            s.push_str(&format!("OOM kills since boot: {kills}\n"));
+        }
+        s
+    }
+}
@@ -0,0 +1,301 @@
+use crate::Monitorable;
+use crate::model::Alert;
+use crate::monitor::load::format_duration;
+use crate::monitor::{
+    MonitorExecConfig, RemoteOs, ToolCaps, elapsed, parse_uptime, rate, shell_quote,
+};
+use anyhow::Result;
+use serde::Deserialize;
+
+/// `[servers.monitor_opts.process]`
+///
+/// ```toml
+/// [servers.monitor_opts.process]
+/// watch = [{ Name = "nginx" }, { Regex = "java.*kafka" }, { Pidfile = "/run/postgresql/15-main.pid" }]
+/// ```
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ProcessOpts {
+    #[serde(default)]
+    pub watch: Vec<ProcessPattern>,
+}
+
+impl ProcessOpts {
+    pub fn validate(&self) -> Result<()> {
+        for pattern in &self.watch {
+            match pattern {
+                ProcessPattern::Name(name) if name.is_empty() || name.contains('/') => {
+                    anyhow::bail!("invalid process name in watch: {name:?}");
+                }
+                ProcessPattern::Regex(re) => {
+                    regex::Regex::new(re)
+                        .map_err(|e| anyhow::anyhow!("invalid process regex {re:?}: {e}"))?;
+                }
+                ProcessPattern::Pidfile(path) if !path.starts_with('/') => {
+                    anyhow::bail!("process pidfile must be an absolute path: {path:?}");
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 要监视的进程
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub enum ProcessPattern {
+    /// 与进程名（comm，最多 15 个字符）完全相同，等同 `pgrep -x`
+    Name(String),
+    /// 匹配完整命令行，等同 `pgrep -f`
+    Regex(String),
+    /// 从 pidfile 首行读取 PID
+    Pidfile(String),
+}
+
+impl ProcessPattern {
+    fn pids_cmd(&self) -> String {
+        match self {
+            ProcessPattern::Name(name) => format!("pgrep -x {}", shell_quote(name)),
+            ProcessPattern::Regex(re) => format!("pgrep -f {}", shell_quote(re)),
+            ProcessPattern::Pidfile(path) => format!("head -n 1 {} 2>/dev/null", shell_quote(path)),
+        }
+    }
+}
+
+impl std::fmt::Display for ProcessPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProcessPattern::Name(s) | ProcessPattern::Regex(s) | ProcessPattern::Pidfile(s) => {
+                f.write_str(s)
+            }
+        }
+    }
+}
+
+/// 匹配到的一个进程
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProcessInstance {
+    pub pid: u32,
+    pub name: String,
+    /// 累计 CPU 时间（utime + stime，jiffies）
+    pub cpu_ticks: u64,
+    /// 开机后第几个 jiffy 启动，与 PID 一起标识同一个进程
+    pub start_ticks: u64,
+    pub cpu_percent: f64,
+    pub rss_kb: u64,
+    pub threads: u32,
+    /// 无权读取 `/proc/<pid>/fd` 时为 None
+    pub fds: Option<u32>,
+    /// 运行时长（秒）
+    pub uptime: f64,
+}
+
+impl ProcessInstance {
+    /// `<pid> <fd 数或 -> <pid> (comm) state ppid ...`；进程已退出时 stat 为空
+    fn parse(line: &str, uptime: f64, caps: &ToolCaps) -> Option<Self> {
+        let (open, close) = (line.find('(')?, line.rfind(')')?);
+        let mut head = line[..open].split_whitespace();
+        let pid = head.next()?.parse().ok()?;
+        let fds = head.next()?.parse().ok();
+        // 从第 3 个字段 state 开始
+        let fields: Vec<&str> = line[close + 1..].split_whitespace().collect();
+        let field = |n: usize| -> Option<u64> { fields.get(n - 3)?.parse().ok() };
+        let start_ticks = field(22)?;
+        Some(Self {
+            pid,
+            name: line[open + 1..close].to_string(),
+            cpu_ticks: field(14)? + field(15)?,
+            start_ticks,
+            cpu_percent: 0.0,
+            rss_kb: field(24)? * caps.page_kb,
+            threads: field(20)? as u32,
+            fds,
+            uptime: (uptime - start_ticks as f64 / caps.clk_tck as f64).max(0.0),
+        })
+    }
+}
+
+/// 一个监视项的采样结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchedProcess {
+    pub pattern: ProcessPattern,
+    pub instances: Vec<ProcessInstance>,
+}
+
+impl WatchedProcess {
+    /// 最早启动的实例，通常是主进程
+    pub fn main(&self) -> Option<&ProcessInstance> {
+        self.instances.iter().min_by_key(|p| (p.start_ticks, p.pid))
+    }
+
+    pub fn cpu_percent(&self) -> f64 {
+        self.instances.iter().map(|p| p.cpu_percent).sum()
+    }
+
+    pub fn rss_kb(&self) -> u64 {
+        self.instances.iter().map(|p| p.rss_kb).sum()
+    }
+
+    pub fn threads(&self) -> u32 {
+        self.instances.iter().map(|p| p.threads).sum()
+    }
+
+    /// 任一实例无法读取时为 None
+    pub fn fds(&self) -> Option<u32> {
+        self.instances.iter().map(|p| p.fds).sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProcessWatchInfo {
+    pub processes: Vec<WatchedProcess>,
+    /// 采样时远端的时间戳（秒），见 [`RemoteOs::clock_cmd`]
+    pub uptime: f64,
+    /// 远端的 CLK_TCK，用于把 jiffies 换算为秒
+    pub clk_tck: u64,
+}
+
+/// 远端脚本中的辅助函数名；`pgrep -f` 会匹配到命令行里带着正则的本脚本，据此排除
+const HELPER: &str = "__stalking_proc";
+
+impl Monitorable for ProcessWatchInfo {
+    fn supported(&self, os: RemoteOs) -> bool {
+        os == RemoteOs::Linux
+    }
+
+    fn exec_cmd(&self, config: &MonitorExecConfig) -> String {
+        let clock = config.os.clock_cmd();
+        let mut cmd = format!(
+            "{clock}; {HELPER}() {{ for p; do \
+             grep -q {HELPER} /proc/$p/cmdline 2>/dev/null && continue; \
+             echo \"$p $([ -r /proc/$p/fd ] && ls /proc/$p/fd | wc -l || echo -) $(cat /proc/$p/stat 2>/dev/null)\"; \
+             done; }}"
+        );
+        for (idx, pattern) in config.opts.process.watch.iter().enumerate() {
+            cmd.push_str(&format!(
+                "; echo '--- {idx}'; {HELPER} $({})",
+                pattern.pids_cmd()
+            ));
+        }
+        cmd.push_str("; true");
+        cmd
+    }
+
+    fn parse_from_str(&mut self, s: &str, config: &MonitorExecConfig) -> Result<()> {
+        let uptime = parse_uptime(s)?;
+        let watch = &config.opts.process.watch;
+        let mut processes: Vec<WatchedProcess> = watch
+            .iter()
+            .map(|pattern| WatchedProcess {
+                pattern: pattern.clone(),
+                instances: Vec::new(),
+            })
+            .collect();
+
+        let mut current = None;
+        for line in s.lines().skip(1) {
+            if let Some(idx) = line.strip_prefix("--- ") {
+                current = idx.trim().parse::<usize>().ok();
+                continue;
+            }
+            let Some(watched) = current.and_then(|idx| processes.get_mut(idx)) else {
+                continue;
+            };
+            if let Some(mut instance) = ProcessInstance::parse(line, uptime, &config.caps) {
+                // 首次采样没有参照，先给出进程生命周期内的平均值
+                if instance.uptime > 0.0 {
+                    instance.cpu_percent =
+                        instance.cpu_ticks as f64 / config.caps.clk_tck as f64 / instance.uptime
+                            * 100.0;
+                }
+                watched.instances.push(instance);
+            }
+        }
+        for watched in &mut processes {
+            watched.instances.sort_by_key(|p| p.pid);
+        }
+        self.processes = processes;
+        self.uptime = uptime;
+        self.clk_tck = config.caps.clk_tck;
+        Ok(())
+    }
+
+    fn apply_previous(&mut self, prev: &Self) {
+        let Some(dt) = elapsed(self.uptime, prev.uptime) else {
+            return;
+        };
+        for watched in &mut self.processes {
+            let Some(old) = prev.processes.iter().find(|p| p.pattern == watched.pattern) else {
+                continue;
+            };
+            for instance in &mut watched.instances {
+                // PID 被复用时启动时间不同，不能求差
+                if let Some(before) = old
+                    .instances
+                    .iter()
+                    .find(|p| p.pid == instance.pid && p.start_ticks == instance.start_ticks)
+                    && let Some(ticks) = rate(instance.cpu_ticks, before.cpu_ticks, dt)
+                {
+                    instance.cpu_percent = ticks / self.clk_tck as f64 * 100.0;
+                }
+            }
+        }
+    }
+
+    fn alerts(&self, prev: &Self) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        for watched in &self.processes {
+            let Some(before) = prev
+                .processes
+                .iter()
+                .find(|p| p.pattern == watched.pattern)
+                .and_then(WatchedProcess::main)
+            else {
+                continue;
+            };
+            let process = watched.pattern.to_string();
+            match watched.main() {
+                None => alerts.push(Alert::ProcessGone {
+                    process,
+                    pid: before.pid,
+                }),
+                // 工作进程轮换不算重启，只看最早启动的主进程
+                Some(now) if now.pid != before.pid || now.start_ticks != before.start_ticks => {
+                    alerts.push(Alert::ProcessRestarted {
+                        process,
+                        old_pid: before.pid,
+                        new_pid: now.pid,
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        alerts
+    }
+
+    fn common_display(&self) -> String {
+        let mut s = format!(
+            "{:<24} {:<7} {:>5} {:>7} {:>10} {:>7} {:>6}  {}\n",
+            "Process", "State", "Count", "CPU%", "RSS", "Threads", "FDs", "Uptime"
+        );
+        for p in &self.processes {
+            let label = p.pattern.to_string();
+            let Some(main) = p.main() else {
+                s.push_str(&format!("{label:<24} {:<7} {:>5}\n", "DOWN", 0));
+                continue;
+            };
+            let fds = p.fds().map_or("-".to_string(), |n| n.to_string());
+            s.push_str(&format!(
+                "{label:<24} {:<7} {:>5} {:>6.1}% {:>8.1}MB {:>7} {:>6}  {} (PID {})\n",
+                "running",
+                p.instances.len(),
+                p.cpu_percent(),
+                p.rss_kb() as f64 / 1024.0,
+                p.threads(),
+                fds,
+                format_duration(main.uptime),
+                main.pid
+            ));
+        }
+        s
+    }
+}
@@ -0,0 +1,261 @@
+use crate::Monitorable;
+use crate::monitor::{MonitorExecConfig, RemoteOs};
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+/// 读取的 sysfs 文件；不存在的通配符由 grep 忽略，虚拟机上通常什么都没有。
+///
+/// 较旧的内核与部分驱动把属性放在 `hwmonN/device/` 下，两处都读；同名时以 `hwmonN/` 为准
+const SENSOR_FILES: [&str; 17] = [
+    "hwmon/hwmon*/name",
+    "hwmon/hwmon*/temp*_input",
+    "hwmon/hwmon*/temp*_label",
+    "hwmon/hwmon*/temp*_max",
+    "hwmon/hwmon*/temp*_crit",
+    "hwmon/hwmon*/fan*_input",
+    "hwmon/hwmon*/fan*_label",
+    "hwmon/hwmon*/device/name",
+    "hwmon/hwmon*/device/temp*_input",
+    "hwmon/hwmon*/device/temp*_label",
+    "hwmon/hwmon*/device/temp*_max",
+    "hwmon/hwmon*/device/temp*_crit",
+    "hwmon/hwmon*/device/fan*_input",
+    "hwmon/hwmon*/device/fan*_label",
+    "thermal/thermal_zone*/type",
+    "thermal/thermal_zone*/temp",
+    "thermal/thermal_zone*/trip_point_*",
+];
+
+/// 远端 sysfs 的 `/sys/class`，测试时换成采集下来的目录树，见 [`sensors_cmd`]
+const SYSFS_CLASS: &str = "/sys/class";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Temperature {
+    /// hwmon 的 `name` 或 thermal zone 的目录名
+    pub chip: String,
+    pub label: String,
+    pub celsius: f64,
+    /// hwmon 的 `temp*_max`，或 thermal zone 中 passive/hot 触发点
+    pub high: Option<f64>,
+    /// hwmon 的 `temp*_crit`，或 thermal zone 中 critical 触发点
+    pub critical: Option<f64>,
+}
+
+impl Temperature {
+    fn status(&self) -> &'static str {
+        match (self.high, self.critical) {
+            (_, Some(crit)) if self.celsius >= crit => "CRITICAL",
+            (Some(high), _) if self.celsius >= high => "HOT",
+            _ => "",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fan {
+    pub chip: String,
+    pub label: String,
+    pub rpm: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SensorsInfo {
+    pub temperatures: Vec<Temperature>,
+    pub fans: Vec<Fan>,
+}
+
+/// 一个 hwmon 或 thermal zone 目录中读到的文件，按文件名索引
+#[derive(Default)]
+struct SensorDir {
+    files: BTreeMap<String, String>,
+}
+
+impl SensorDir {
+    fn get(&self, file: &str) -> Option<&str> {
+        self.files.get(file).map(String::as_str)
+    }
+
+    /// sysfs 的温度单位是毫摄氏度
+    fn millidegrees(&self, file: &str) -> Option<f64> {
+        Some(self.get(file)?.trim().parse::<f64>().ok()? / 1000.0)
+    }
+
+    /// `temp3_input` 等文件中出现的传感器编号，按数值排序
+    fn indexes(&self, prefix: &str, suffix: &str) -> Vec<u32> {
+        let mut indexes: Vec<u32> = self
+            .files
+            .keys()
+            .filter_map(|f| f.strip_prefix(prefix)?.strip_suffix(suffix)?.parse().ok())
+            .collect();
+        indexes.sort_unstable();
+        indexes
+    }
+}
+
+/// 目录名末尾的编号，使 hwmon10 排在 hwmon2 之后
+fn dir_index(name: &str, prefix: &str) -> Option<u32> {
+    name.strip_prefix(prefix)?.parse().ok()
+}
+
+impl SensorsInfo {
+    /// `grep -H . <files>` 的输出：每行 `<path>:<value>`
+    fn parse_grep(s: &str) -> Self {
+        let mut hwmon: BTreeMap<u32, SensorDir> = BTreeMap::new();
+        let mut thermal: BTreeMap<u32, SensorDir> = BTreeMap::new();
+        for line in s.lines() {
+            let Some((path, value)) = line.split_once(':') else {
+                continue;
+            };
+            let mut parts = path.rsplit('/');
+            let (Some(file), Some(mut dir)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            // `hwmonN/device/temp1_input` 归入 hwmonN
+            if dir == "device" {
+                let Some(parent) = parts.next() else {
+                    continue;
+                };
+                dir = parent;
+            }
+            let target = if let Some(idx) = dir_index(dir, "hwmon") {
+                hwmon.entry(idx).or_default()
+            } else if let Some(idx) = dir_index(dir, "thermal_zone") {
+                thermal.entry(idx).or_default()
+            } else {
+                continue;
+            };
+            // grep 按参数顺序输出，`hwmonN/` 下的文件先于 `device/` 中的同名文件
+            target
+                .files
+                .entry(file.to_string())
+                .or_insert_with(|| value.trim().to_string());
+        }
+
+        let mut info = Self::default();
+        for (idx, dir) in &hwmon {
+            let chip = dir
+                .get("name")
+                .map_or_else(|| format!("hwmon{idx}"), str::to_string);
+            for n in dir.indexes("temp", "_input") {
+                let Some(celsius) = dir.millidegrees(&format!("temp{n}_input")) else {
+                    continue;
+                };
+                info.temperatures.push(Temperature {
+                    chip: chip.clone(),
+                    label: dir
+                        .get(&format!("temp{n}_label"))
+                        .map_or_else(|| format!("temp{n}"), str::to_string),
+                    celsius,
+                    high: dir.millidegrees(&format!("temp{n}_max")),
+                    critical: dir.millidegrees(&format!("temp{n}_crit")),
+                });
+            }
+            for n in dir.indexes("fan", "_input") {
+                let Some(rpm) = dir
+                    .get(&format!("fan{n}_input"))
+                    .and_then(|v| v.parse().ok())
+                else {
+                    continue;
+                };
+                info.fans.push(Fan {
+                    chip: chip.clone(),
+                    label: dir
+                        .get(&format!("fan{n}_label"))
+                        .map_or_else(|| format!("fan{n}"), str::to_string),
+                    rpm,
+                });
+            }
+        }
+        for (idx, dir) in &thermal {
+            let Some(celsius) = dir.millidegrees("temp") else {
+                continue;
+            };
+            // 同类触发点可能有多个，取最低的一个
+            let trip = |kinds: &[&str]| {
+                dir.indexes("trip_point_", "_type")
+                    .into_iter()
+                    .filter(|n| {
+                        dir.get(&format!("trip_point_{n}_type"))
+                            .is_some_and(|t| kinds.contains(&t))
+                    })
+                    .filter_map(|n| dir.millidegrees(&format!("trip_point_{n}_temp")))
+                    .filter(|t| *t > 0.0)
+                    .reduce(f64::min)
+            };
+            info.temperatures.push(Temperature {
+                chip: format!("thermal_zone{idx}"),
+                label: dir.get("type").unwrap_or("thermal").to_string(),
+                celsius,
+                high: trip(&["passive", "hot"]),
+                critical: trip(&["critical"]),
+            });
+        }
+        info
+    }
+}
+
+/// 在 `class_dir` 下读取所有传感器文件的命令
+pub(crate) fn sensors_cmd(class_dir: &str) -> String {
+    let files: Vec<String> = SENSOR_FILES
+        .iter()
+        .map(|f| format!("{class_dir}/{f}"))
+        .collect();
+    format!("grep -H . {} 2>/dev/null; true", files.join(" "))
+}
+
+impl Monitorable for SensorsInfo {
+    fn supported(&self, os: RemoteOs) -> bool {
+        os == RemoteOs::Linux
+    }
+
+    fn exec_cmd(&self, _config: &MonitorExecConfig) -> String {
+        sensors_cmd(SYSFS_CLASS)
+    }
+
+    fn parse_from_str(&mut self, s: &str, _config: &MonitorExecConfig) -> Result<()> {
+        *self = Self::parse_grep(s);
+        Ok(())
+    }
+
+    fn common_display(&self) -> String {
+        if self.temperatures.is_empty() && self.fans.is_empty() {
+            // 虚拟机与容器通常没有 hwmon，这不是错误
+            return "No hardware sensors found (virtual machine?)\n".to_string();
+        }
+        let mut s = String::new();
+        if !self.temperatures.is_empty() {
+            s.push_str("Temperatures:\n");
+            for t in &self.temperatures {
+                let limits: Vec<String> = [("high", t.high), ("crit", t.critical)]
+                    .into_iter()
+                    .filter_map(|(name, v)| Some(format!("{name} {:.1}°C", v?)))
+                    .collect();
+                let limits = if limits.is_empty() {
+                    String::new()
+                } else {
+                    format!("({})", limits.join(", "))
+                };
+                let line = format!(
+                    "  {:<14} {:<16} {:>6.1}°C  {:<28} {}",
+                    t.chip,
+                    t.label,
+                    t.celsius,
+                    limits,
+                    t.status()
+                );
+                s.push_str(line.trim_end());
+                s.push('\n');
+            }
+        }
+        if !self.fans.is_empty() {
+            s.push_str("Fans:\n");
+            for f in &self.fans {
+                s.push_str(&format!(
+                    "  {:<14} {:<16} {:>6} RPM\n",
+                    f.chip, f.label, f.rpm
+                ));
+            }
+        }
+        s
+    }
+}
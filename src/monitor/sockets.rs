@@ -0,0 +1,274 @@
+use crate::Monitorable;
+use crate::model::Alert;
+use crate::monitor::{MonitorExecConfig, RemoteOs, elapsed, parse_uptime, rate};
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
+
+/// 一个处于监听状态的套接字；UDP 为已绑定、未连接的套接字
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListenSocket {
+    /// `tcp` 或 `udp`
+    pub proto: String,
+    /// 监听地址，如 `0.0.0.0`、`[::]`、`127.0.0.53%lo`
+    pub address: String,
+    pub port: u16,
+    /// 进程名与 PID；非 root 用户看不到其他用户的进程
+    pub process: Option<(String, u32)>,
+}
+
+impl ListenSocket {
+    /// `ss -tulnp` 的一行：`Netid State Recv-Q Send-Q Local Peer [users:(("name",pid=1,fd=3),...)]`
+    fn parse(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let proto = *fields.first()?;
+        if proto != "tcp" && proto != "udp" {
+            return None;
+        }
+        let (address, port) = fields.get(4)?.rsplit_once(':')?;
+        // 进程名可能含空格，不能按列切分
+        let process = line.split_once("users:((\"").and_then(|(_, users)| {
+            let (name, rest) = users.split_once('"')?;
+            let pid = rest.strip_prefix(",pid=")?;
+            let pid = pid
+                .split(|c: char| !c.is_ascii_digit())
+                .next()?
+                .parse()
+                .ok()?;
+            Some((name.to_string(), pid))
+        });
+        Some(Self {
+            proto: proto.to_string(),
+            address: address.to_string(),
+            port: port.parse().ok()?,
+            process,
+        })
+    }
+
+    /// 两次采样中是否为同一个监听端口，不比较进程
+    fn same_port(&self, other: &Self) -> bool {
+        self.proto == other.proto && self.address == other.address && self.port == other.port
+    }
+}
+
+impl std::fmt::Display for ListenSocket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}:{}", self.proto, self.address, self.port)?;
+        if let Some((name, pid)) = &self.process {
+            write!(f, " ({name}, PID {pid})")?;
+        }
+        Ok(())
+    }
+}
+
+/// `/proc/net/snmp` 与 `/proc/net/netstat` 中的累计计数器
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SocketCounters {
+    pub out_segs: u64,
+    pub retrans_segs: u64,
+    /// accept 队列已满而丢弃的连接
+    pub listen_overflows: u64,
+    pub udp_in_errors: u64,
+}
+
+impl SocketCounters {
+    /// 两行一组：`Tcp: <字段名...>` 后跟 `Tcp: <数值...>`
+    fn parse(s: &str) -> Self {
+        let mut values: HashMap<String, u64> = HashMap::new();
+        let lines: Vec<&str> = s.lines().collect();
+        for pair in lines.windows(2) {
+            let (Some((group, names)), Some((value_group, nums))) =
+                (pair[0].split_once(':'), pair[1].split_once(':'))
+            else {
+                continue;
+            };
+            if group != value_group || names.trim_start().starts_with(|c: char| c.is_ascii_digit())
+            {
+                continue;
+            }
+            // Tcp 的 MaxConn 为 -1，解析失败的字段直接忽略
+            for (name, num) in names.split_whitespace().zip(nums.split_whitespace()) {
+                if let Ok(num) = num.parse() {
+                    values.insert(format!("{group}.{name}"), num);
+                }
+            }
+        }
+        let get = |key: &str| values.get(key).copied().unwrap_or(0);
+        Self {
+            out_segs: get("Tcp.OutSegs"),
+            retrans_segs: get("Tcp.RetransSegs"),
+            listen_overflows: get("TcpExt.ListenOverflows"),
+            udp_in_errors: get("Udp.InErrors"),
+        }
+    }
+}
+
+/// 单独列出的 TCP 状态，其余状态合计为 other
+const KEY_STATES: [&str; 3] = ["ESTAB", "TIME-WAIT", "CLOSE-WAIT"];
+
+/// 读不到 `ip_local_port_range` 时使用的内核默认下限
+const DEFAULT_EPHEMERAL_FROM: u16 = 32768;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SocketsInfo {
+    /// `ss -tan` 各状态的连接数；远端没有 ss（如 BusyBox）时为 None
+    pub tcp_states: Option<BTreeMap<String, u32>>,
+    /// 远端没有 ss 时为 None。不含临时端口上的 UDP 套接字：DNS 查询等客户端
+    /// 也处于 UNCONN 状态，每次采样都不同
+    pub listening: Option<Vec<ListenSocket>>,
+    pub counters: SocketCounters,
+    /// 每秒重传的 TCP 段数
+    pub retrans_rate: f64,
+    /// 重传段占发送段的百分比
+    pub retrans_percent: f64,
+    pub listen_overflow_rate: f64,
+    pub udp_error_rate: f64,
+    /// 采样时远端的时间戳（秒），见 [`RemoteOs::clock_cmd`]
+    pub uptime: f64,
+}
+
+impl SocketsInfo {
+    /// 由计数器在 `dt` 秒内的变化计算速率；计数器回退时保留原值
+    fn update_rates(&mut self, prev: &SocketCounters, dt: f64) {
+        let cur = &self.counters;
+        if let (Some(out), Some(retrans), Some(overflows), Some(udp_errors)) = (
+            rate(cur.out_segs, prev.out_segs, dt),
+            rate(cur.retrans_segs, prev.retrans_segs, dt),
+            rate(cur.listen_overflows, prev.listen_overflows, dt),
+            rate(cur.udp_in_errors, prev.udp_in_errors, dt),
+        ) {
+            self.retrans_rate = retrans;
+            self.retrans_percent = if out > 0.0 {
+                retrans / out * 100.0
+            } else {
+                0.0
+            };
+            self.listen_overflow_rate = overflows;
+            self.udp_error_rate = udp_errors;
+        }
+    }
+}
+
+impl Monitorable for SocketsInfo {
+    fn supported(&self, os: RemoteOs) -> bool {
+        os == RemoteOs::Linux
+    }
+
+    fn exec_cmd(&self, config: &MonitorExecConfig) -> String {
+        // 只传回各状态的计数；连接很多时 `ss -tan` 的完整输出可达数 MB
+        let clock = config.os.clock_cmd();
+        format!(
+            "{clock}; cat /proc/net/snmp /proc/net/netstat; echo '---'; \
+             ss -tan 2>/dev/null | awk 'NR > 1 {{ n[$1]++ }} END {{ for (s in n) print s, n[s] }}'; \
+             echo '---'; ss -tulnp 2>/dev/null; \
+             echo '---'; cat /proc/sys/net/ipv4/ip_local_port_range 2>/dev/null; true"
+        )
+    }
+
+    fn parse_from_str(&mut self, s: &str, _config: &MonitorExecConfig) -> Result<()> {
+        let mut sections = s.split("\n---\n");
+        let snmp = sections.next().unwrap_or_default();
+        let states = sections.next().unwrap_or_default();
+        let listen = sections.next().unwrap_or_default();
+        let ephemeral_from = sections
+            .next()
+            .and_then(|range| range.split_whitespace().next()?.parse().ok())
+            .unwrap_or(DEFAULT_EPHEMERAL_FROM);
+        let uptime = parse_uptime(snmp)?;
+
+        *self = Self::default();
+        self.counters = SocketCounters::parse(snmp);
+        // 有 ss 时总会输出表头，以此区分“没有 ss”与“没有连接”
+        if listen.trim_start().starts_with("Netid") {
+            self.tcp_states = Some(
+                states
+                    .lines()
+                    .filter_map(|line| {
+                        let (state, count) = line.split_once(' ')?;
+                        Some((state.to_string(), count.trim().parse().ok()?))
+                    })
+                    .collect(),
+            );
+            let mut listening: Vec<ListenSocket> = Vec::new();
+            // SO_REUSEPORT 时同一端口有多个套接字，只保留一个
+            for socket in listen.lines().skip(1).filter_map(ListenSocket::parse) {
+                if socket.proto == "udp" && socket.port >= ephemeral_from {
+                    continue;
+                }
+                if !listening.iter().any(|l| l.same_port(&socket)) {
+                    listening.push(socket);
+                }
+            }
+            listening.sort_by(|a, b| {
+                (&a.proto, a.port, &a.address).cmp(&(&b.proto, b.port, &b.address))
+            });
+            self.listening = Some(listening);
+        }
+        // 首次采样没有参照，先给出开机以来的平均值
+        self.update_rates(&SocketCounters::default(), uptime.max(1.0));
+        self.uptime = uptime;
+        Ok(())
+    }
+
+    fn apply_previous(&mut self, prev: &Self) {
+        if let Some(dt) = elapsed(self.uptime, prev.uptime) {
+            self.update_rates(&prev.counters, dt);
+        }
+    }
+
+    fn alerts(&self, prev: &Self) -> Vec<Alert> {
+        let (Some(now), Some(before)) = (&self.listening, &prev.listening) else {
+            return Vec::new();
+        };
+        let mut alerts: Vec<Alert> = now
+            .iter()
+            .filter(|s| !before.iter().any(|b| b.same_port(s)))
+            .map(|s| Alert::PortOpened { socket: s.clone() })
+            .collect();
+        alerts.extend(
+            before
+                .iter()
+                .filter(|b| !now.iter().any(|s| s.same_port(b)))
+                .map(|b| Alert::PortClosed { socket: b.clone() }),
+        );
+        alerts
+    }
+
+    fn common_display(&self) -> String {
+        let mut s = String::new();
+        match &self.tcp_states {
+            Some(states) => {
+                let count = |state: &str| states.get(state).copied().unwrap_or(0);
+                let other: u32 = states
+                    .iter()
+                    .filter(|(state, _)| !KEY_STATES.contains(&state.as_str()))
+                    .map(|(_, n)| n)
+                    .sum();
+                s.push_str(&format!(
+                    "TCP: {} established, {} time-wait, {} close-wait, {other} other\n",
+                    count("ESTAB"),
+                    count("TIME-WAIT"),
+                    count("CLOSE-WAIT")
+                ));
+            }
+            None => s.push_str("TCP states not available (ss not found)\n"),
+        }
+        s.push_str(&format!(
+            "Retransmits: {:.1} segs/s ({:.2}%), listen overflows: {:.1}/s, UDP errors: {:.1}/s\n",
+            self.retrans_rate, self.retrans_percent, self.listen_overflow_rate, self.udp_error_rate
+        ));
+        if let Some(listening) = &self.listening {
+            s.push_str(&format!("Listening ({}):\n", listening.len()));
+            for l in listening {
+                let process = l
+                    .process
+                    .as_ref()
+                    .map_or("-".to_string(), |(name, pid)| format!("{name} ({pid})"));
+                s.push_str(&format!(
+                    "  {:<4} {:>24}:{:<5} {process}\n",
+                    l.proto, l.address, l.port
+                ));
+            }
+        }
+        s
+    }
+}
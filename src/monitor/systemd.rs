@@ -0,0 +1,227 @@
+use crate::Monitorable;
+use crate::monitor::load::format_duration;
+use crate::monitor::{MonitorExecConfig, RemoteOs, elapsed, parse_uptime, rate, shell_quote};
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// `[servers.monitor_opts.systemd]`
+#[derive(Debug, Deserialize, Clone)]
+pub struct SystemdOpts {
+    /// 要显示的 unit，省略后缀时按 `.service` 处理
+    #[serde(default)]
+    pub units: Vec<String>,
+    /// 同时显示所有处于 failed 状态的 unit
+    #[serde(default = "default_include_failed")]
+    pub include_failed: bool,
+}
+
+impl Default for SystemdOpts {
+    fn default() -> Self {
+        Self {
+            units: Vec::new(),
+            include_failed: default_include_failed(),
+        }
+    }
+}
+
+fn default_include_failed() -> bool {
+    true
+}
+
+/// `systemctl show` 读取的属性
+const PROPERTIES: &str = "Id,Description,LoadState,ActiveState,SubState,NRestarts,\
+MemoryCurrent,CPUUsageNSec,StateChangeTimestamp";
+
+/// 在 `LC_ALL=C TZ=UTC` 下 `systemctl show` 输出的时间格式，如 `Tue 2023-11-14 22:13:20 UTC`
+const TIMESTAMP_FORMAT: &str = "%a %Y-%m-%d %H:%M:%S UTC";
+
+/// 远端没有 systemctl 时输出的标记
+const NO_SYSTEMD: &str = "no-systemctl";
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SystemdUnit {
+    pub id: String,
+    pub description: String,
+    /// `loaded`、`not-found` 等
+    pub load_state: String,
+    /// `active`、`failed`、`activating` 等
+    pub active_state: String,
+    /// `running`、`exited`、`dead` 等
+    pub sub_state: String,
+    /// systemd 235 以前没有
+    pub restarts: Option<u32>,
+    /// 未开启 MemoryAccounting 时为 None
+    pub memory_bytes: Option<u64>,
+    /// 未开启 CPUAccounting 时为 None
+    pub cpu_nsec: Option<u64>,
+    /// 由相邻两次采样的 CPUUsageNSec 求得
+    pub cpu_percent: Option<f64>,
+    /// 距上次状态变化的秒数
+    pub since_change: Option<f64>,
+}
+
+impl SystemdUnit {
+    /// `now` 为远端 `date +%s` 的结果
+    fn from_properties(props: &HashMap<&str, &str>, now: i64) -> Option<Self> {
+        // 未设置的计数器显示为 `[not set]` 或 u64::MAX
+        let counter =
+            |key: &str| -> Option<u64> { props.get(key)?.parse().ok().filter(|v| *v != u64::MAX) };
+        let text = |key: &str| props.get(key).unwrap_or(&"").to_string();
+        Some(Self {
+            id: props.get("Id")?.to_string(),
+            description: text("Description"),
+            load_state: text("LoadState"),
+            active_state: text("ActiveState"),
+            sub_state: text("SubState"),
+            restarts: counter("NRestarts").map(|n| n as u32),
+            memory_bytes: counter("MemoryCurrent"),
+            cpu_nsec: counter("CPUUsageNSec"),
+            cpu_percent: None,
+            // 单调时钟不含系统挂起的时间，改用墙上时间与远端当前时间比较；从未变化时为空
+            since_change: props
+                .get("StateChangeTimestamp")
+                .and_then(|v| NaiveDateTime::parse_from_str(v, TIMESTAMP_FORMAT).ok())
+                .map(|at| (now - at.and_utc().timestamp()).max(0) as f64),
+        })
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active_state == "active"
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.active_state == "failed"
+    }
+
+    /// 表格中的一行：Unit、Active、Sub、Restarts、Memory、CPU%、Since
+    pub fn columns(&self) -> [String; 7] {
+        let or_dash = |v: Option<String>| v.unwrap_or_else(|| "-".to_string());
+        let state = if self.load_state == "not-found" {
+            "not-found".to_string()
+        } else {
+            self.active_state.clone()
+        };
+        [
+            self.id.clone(),
+            state,
+            self.sub_state.clone(),
+            or_dash(self.restarts.map(|n| n.to_string())),
+            or_dash(
+                self.memory_bytes
+                    .map(|b| format!("{:.1}MB", b as f64 / 1_048_576.0)),
+            ),
+            or_dash(self.cpu_percent.map(|p| format!("{p:.1}%"))),
+            or_dash(self.since_change.map(format_duration)),
+        ]
+    }
+}
+
+pub const UNIT_HEADERS: [&str; 7] = [
+    "Unit", "Active", "Sub", "Restarts", "Memory", "CPU%", "Since",
+];
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SystemdInfo {
+    pub units: Vec<SystemdUnit>,
+    /// 采样时远端的时间戳（秒），见 [`RemoteOs::clock_cmd`]
+    pub uptime: f64,
+}
+
+impl SystemdInfo {
+    /// 各状态的 unit 数；TUI 用表格显示 unit 时只输出这一行
+    pub fn summary(&self) -> String {
+        let active = self.units.iter().filter(|u| u.is_active()).count();
+        let failed = self.units.iter().filter(|u| u.is_failed()).count();
+        format!(
+            "Units: {active} active, {failed} failed, {} other\n",
+            self.units.len() - active - failed
+        )
+    }
+}
+
+impl Monitorable for SystemdInfo {
+    fn supported(&self, os: RemoteOs) -> bool {
+        os == RemoteOs::Linux
+    }
+
+    fn exec_cmd(&self, config: &MonitorExecConfig) -> String {
+        let opts = &config.opts.systemd;
+        let mut units: Vec<String> = opts.units.iter().map(|u| shell_quote(u)).collect();
+        if opts.include_failed {
+            units.push(
+                "$(systemctl list-units --state=failed --no-legend --plain | awk '{print $1}')"
+                    .to_string(),
+            );
+        }
+        // 没有任何 unit 时 `systemctl show` 会输出 manager 自身的属性
+        format!(
+            "{}; date +%s; if command -v systemctl >/dev/null 2>&1; then set -- {}; \
+             [ $# -eq 0 ] || LC_ALL=C TZ=UTC systemctl show -p {PROPERTIES} \"$@\"; \
+             else echo {NO_SYSTEMD}; fi",
+            config.os.clock_cmd(),
+            units.join(" ")
+        )
+    }
+
+    fn parse_from_str(&mut self, s: &str, _config: &MonitorExecConfig) -> Result<()> {
+        let uptime = parse_uptime(s)?;
+        if s.lines().any(|line| line.trim() == NO_SYSTEMD) {
+            anyhow::bail!("systemctl not found on the remote host");
+        }
+        let now: i64 = s
+            .lines()
+            .nth(1)
+            .and_then(|line| line.trim().parse().ok())
+            .ok_or_else(|| anyhow::anyhow!("missing remote date"))?;
+        // 每个 unit 一段 `Key=Value`，段之间以空行分隔
+        let mut units: Vec<SystemdUnit> = Vec::new();
+        for block in s
+            .lines()
+            .skip(2)
+            .collect::<Vec<_>>()
+            .split(|l| l.trim().is_empty())
+        {
+            let props: HashMap<&str, &str> =
+                block.iter().filter_map(|l| l.split_once('=')).collect();
+            if let Some(unit) = SystemdUnit::from_properties(&props, now)
+                && !units.iter().any(|u| u.id == unit.id)
+            {
+                units.push(unit);
+            }
+        }
+        self.units = units;
+        self.uptime = uptime;
+        Ok(())
+    }
+
+    fn apply_previous(&mut self, prev: &Self) {
+        let Some(dt) = elapsed(self.uptime, prev.uptime) else {
+            return;
+        };
+        for unit in &mut self.units {
+            if let Some(old) = prev.units.iter().find(|u| u.id == unit.id)
+                && let (Some(cur), Some(before)) = (unit.cpu_nsec, old.cpu_nsec)
+            {
+                unit.cpu_percent = rate(cur, before, dt).map(|ns| ns / 1e7);
+            }
+        }
+    }
+
+    fn common_display(&self) -> String {
+        let mut s = self.summary();
+        let row = |c: [&str; 7]| {
+            format!(
+                "{:<32} {:<10} {:<10} {:>8} {:>10} {:>7}  {}\n",
+                c[0], c[1], c[2], c[3], c[4], c[5], c[6]
+            )
+        };
+        s.push_str(&row(UNIT_HEADERS));
+        for unit in &self.units {
+            let c = unit.columns();
+            s.push_str(&row(c.each_ref().map(String::as_str)));
+        }
+        s
+    }
+}
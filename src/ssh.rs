@@ -1,77 +1,353 @@
-use crate::Monitorable;
-use crate::config::ServerConfig;
+use crate::config::{AuthKind, HopConfig, HostKeyCheck, ServerConfig};
+use crate::transport::{CommandOutput, Transport};
 use anyhow::Result;
-use async_ssh2_tokio::{ServerCheckMethod, ToSocketAddrsWithHostname};
+use async_trait::async_trait;
+use russh::ChannelMsg;
+use russh::client::{Config, Handle, Handler};
+use russh::keys::agent::client::AgentClient;
+use russh::keys::{HashAlg, PrivateKeyWithHashAlg, PublicKey};
+use std::collections::HashMap;
+use std::fmt;
 use std::path::Path;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// 主机密钥校验失败（未知主机、密钥变更或指纹不匹配）
+#[derive(Debug)]
+pub struct HostKeyError {
+    pub host: String,
+    pub reason: String,
+    /// 服务器密钥与记录不符；为 false 时表示没有记录或无法读取 known_hosts
+    pub mismatch: bool,
+}
+
+impl fmt::Display for HostKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "host key verification failed for {}: {}",
+            self.host, self.reason
+        )
+    }
+}
+
+impl std::error::Error for HostKeyError {}
+
+/// 在握手阶段按 `HostKeyCheck` 校验服务器公钥
+pub(crate) struct HostKeyVerifier {
+    host: String,
+    port: u16,
+    check: HostKeyCheck,
+}
+
+impl HostKeyVerifier {
+    pub(crate) fn new(host: &str, port: u16, check: HostKeyCheck) -> Self {
+        Self {
+            host: host.to_string(),
+            port,
+            check,
+        }
+    }
+
+    pub(crate) fn verify(&self, key: &PublicKey) -> Result<(), HostKeyError> {
+        let fail = |reason: String, mismatch: bool| HostKeyError {
+            host: format!("{}:{}", self.host, self.port),
+            reason,
+            mismatch,
+        };
+        let known = match &self.check {
+            HostKeyCheck::Insecure => return Ok(()),
+            HostKeyCheck::Fingerprint(expected) => {
+                let actual = key.fingerprint(HashAlg::Sha256).to_string();
+                let expected = expected.trim();
+                let expected = expected.strip_prefix("SHA256:").unwrap_or(expected);
+                return if actual.strip_prefix("SHA256:") == Some(expected) {
+                    Ok(())
+                } else {
+                    Err(fail(
+                        format!("fingerprint mismatch, expected SHA256:{expected}, got {actual}"),
+                        true,
+                    ))
+                };
+            }
+            HostKeyCheck::KnownHosts => russh::keys::check_known_hosts(&self.host, self.port, key),
+            HostKeyCheck::KnownHostsFile(path) => {
+                russh::keys::check_known_hosts_path(&self.host, self.port, key, path)
+            }
+        };
+        match known {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(fail(
+                format!(
+                    "host not found in known_hosts (offered {})",
+                    key.fingerprint(HashAlg::Sha256)
+                ),
+                false,
+            )),
+            Err(russh::keys::Error::KeyChanged { line }) => Err(fail(
+                format!(
+                    "key does not match known_hosts line {line} (offered {})",
+                    key.fingerprint(HashAlg::Sha256)
+                ),
+                true,
+            )),
+            Err(e) => Err(fail(format!("cannot read known_hosts: {e}"), false)),
+        }
+    }
+}
+
+impl Handler for HostKeyVerifier {
+    type Error = anyhow::Error;
+
+    async fn check_server_key(&mut self, server_public_key: &PublicKey) -> Result<bool> {
+        self.verify(server_public_key)?;
+        Ok(true)
+    }
+}
+
+#[cfg(unix)]
+async fn connect_agent() -> Result<AgentClient<tokio::net::UnixStream>> {
+    AgentClient::connect_env()
+        .await
+        .map_err(|e| anyhow::anyhow!("cannot connect to ssh-agent via SSH_AUTH_SOCK: {e}"))
+}
+
+#[cfg(windows)]
+async fn connect_agent() -> Result<AgentClient<tokio::net::windows::named_pipe::NamedPipeClient>> {
+    // Windows 上 OpenSSH agent 监听命名管道，SSH_AUTH_SOCK 可覆盖默认路径
+    let pipe = std::env::var("SSH_AUTH_SOCK")
+        .unwrap_or_else(|_| r"\\.\pipe\openssh-ssh-agent".to_string());
+    AgentClient::connect_named_pipe(&pipe)
+        .await
+        .map_err(|e| anyhow::anyhow!("cannot connect to ssh-agent at {pipe}: {e}"))
+}
+
+/// 按 `hop` 的认证方式完成认证
+async fn authenticate(handle: &mut Handle<HostKeyVerifier>, hop: &HopConfig) -> Result<()> {
+    let user = hop.user.as_str();
+    match hop.auth_kind() {
+        AuthKind::Password => {
+            let pswd = hop
+                .password
+                .as_deref()
+                .ok_or_else(|| anyhow::anyhow!("no password provided for {}", hop.label()))?;
+            let auth = handle.authenticate_password(user, pswd).await?;
+            if !auth.success() {
+                anyhow::bail!("password authentication failed for {}", hop.label());
+            }
+        }
+        AuthKind::Key => {
+            let path = hop
+                .privkey_path
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("no privkey_path provided for {}", hop.label()))?;
+            let key = russh::keys::load_secret_key(path, hop.passphrase.as_deref())?;
+            let hash = handle.best_supported_rsa_hash().await?.flatten();
+            let auth = handle
+                .authenticate_publickey(user, PrivateKeyWithHashAlg::new(Arc::new(key), hash))
+                .await?;
+            if !auth.success() {
+                anyhow::bail!("public key authentication failed for {}", hop.label());
+            }
+        }
+        AuthKind::Agent => {
+            let agent = connect_agent().await?;
+            authenticate_with_agent(handle, user, agent).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn authenticate_with_agent<S>(
+    handle: &mut Handle<HostKeyVerifier>,
+    user: &str,
+    mut agent: AgentClient<S>,
+) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let identities = agent.request_identities().await?;
+    if identities.is_empty() {
+        anyhow::bail!("ssh-agent has no identities");
+    }
+    let hash = handle.best_supported_rsa_hash().await?.flatten();
+    for identity in identities {
+        let auth = handle
+            .authenticate_publickey_with(user, identity, hash, &mut agent)
+            .await?;
+        if auth.success() {
+            return Ok(());
+        }
+    }
+    anyhow::bail!("no ssh-agent identity was accepted for {user}")
+}
+
+/// 跳板连接池：经过同一跳板链前缀的服务器共享同一条 SSH 连接。
+#[derive(Clone, Default)]
+pub struct JumpPool {
+    slots: Arc<std::sync::Mutex<HashMap<String, JumpSlot>>>,
+}
+
+type JumpSlot = Arc<tokio::sync::Mutex<Option<Arc<SSHClient>>>>;
+
+impl JumpPool {
+    /// 依次连接（或复用）跳板链上的每一跳，返回最后一跳的连接
+    pub async fn connect_chain(&self, chain: &[HopConfig]) -> Result<Option<Arc<SSHClient>>> {
+        let mut via: Option<Arc<SSHClient>> = None;
+        let mut key = String::new();
+        for hop in chain {
+            if !key.is_empty() {
+                key.push('>');
+            }
+            key.push_str(&hop.label());
+            let slot = self
+                .slots
+                .lock()
+                .unwrap()
+                .entry(key.clone())
+                .or_default()
+                .clone();
+            // 每个跳板单独加锁：同一跳板只握手一次，不同跳板互不阻塞
+            let mut cached = slot.lock().await;
+            let client = match cached.as_ref() {
+                Some(client) if !client.is_closed() => client.clone(),
+                _ => {
+                    let client = SSHClient::open(hop, via.clone())
+                        .await
+                        .map_err(|e| e.context(format!("jump host {}", hop.label())))?;
+                    cached.insert(Arc::new(client)).clone()
+                }
+            };
+            via = Some(client);
+        }
+        Ok(via)
+    }
+}
 
 pub struct SSHClient {
-    pub(crate) client: async_ssh2_tokio::Client,
+    handle: Handle<HostKeyVerifier>,
+    /// 经由的上一跳连接；持有它以保证隧道在本连接存活期间不被关闭
+    _via: Option<Arc<SSHClient>>,
 }
 
 impl SSHClient {
+    /// 建立到 `hop` 的连接；`via` 不为空时通过它的 direct-tcpip 通道转发
+    async fn open(hop: &HopConfig, via: Option<Arc<SSHClient>>) -> Result<Self> {
+        let verifier = HostKeyVerifier::new(&hop.host, hop.port, hop.host_key_check.clone());
+        let config = Arc::new(Config::default());
+        let mut handle = match &via {
+            None => russh::client::connect(config, (hop.host.as_str(), hop.port), verifier).await?,
+            Some(jump) => {
+                let channel = jump
+                    .handle
+                    .channel_open_direct_tcpip(hop.host.as_str(), hop.port as u32, "127.0.0.1", 0)
+                    .await?;
+                russh::client::connect_stream(config, channel.into_stream(), verifier).await?
+            }
+        };
+        authenticate(&mut handle, hop).await?;
+        Ok(Self { handle, _via: via })
+    }
+
     pub async fn with_pswd(
         pswd: &str,
         user: impl AsRef<str>,
-        addrs: impl ToSocketAddrsWithHostname,
+        (host, port): (&str, u16),
+        check: HostKeyCheck,
     ) -> Result<Self> {
-        let auth_method = async_ssh2_tokio::AuthMethod::with_password(pswd);
-        let client = async_ssh2_tokio::Client::connect(
-            addrs,
-            user.as_ref(),
-            auth_method,
-            ServerCheckMethod::NoCheck,
-        )
-        .await?;
-        Ok(Self { client })
+        let hop = HopConfig {
+            password: Some(pswd.to_string()),
+            auth: Some(AuthKind::Password),
+            ..HopConfig::direct(host, port, user.as_ref(), check)
+        };
+        Self::open(&hop, None).await
     }
 
     pub async fn with_key(
         key_path: impl AsRef<Path>,
         user: impl AsRef<str>,
         passphrase: Option<&str>,
-        addrs: impl ToSocketAddrsWithHostname,
+        (host, port): (&str, u16),
+        check: HostKeyCheck,
     ) -> Result<Self> {
-        let auth_method = async_ssh2_tokio::AuthMethod::with_key_file(key_path, passphrase);
-        let client = async_ssh2_tokio::Client::connect(
-            addrs,
-            user.as_ref(),
-            auth_method,
-            ServerCheckMethod::NoCheck,
-        )
-        .await?;
-        Ok(Self { client })
-    }
-
-    pub async fn connect_from_config(config: &ServerConfig) -> Result<Self> {
-        if let Some(privkey_path) = &config.privkey_path {
-            Self::with_key(
-                privkey_path,
-                &config.user,
-                config.passphrase.as_deref(),
-                (config.host.as_str(), config.port),
-            )
-            .await
-        } else if let Some(pswd) = &config.password {
-            Self::with_pswd(pswd, &config.user, (config.host.as_str(), config.port)).await
-        } else {
-            Err(anyhow::anyhow!(
-                "no authentication method provided for server {}",
-                config.name
-            ))
-        }
+        let hop = HopConfig {
+            privkey_path: Some(key_path.as_ref().to_path_buf()),
+            passphrase: passphrase.map(str::to_string),
+            auth: Some(AuthKind::Key),
+            ..HopConfig::direct(host, port, user.as_ref(), check)
+        };
+        Self::open(&hop, None).await
     }
 
-    pub async fn exec<T: Monitorable>(&self, mut monitor: T) -> Result<T> {
-        let result = self.client.execute(monitor.exec_cmd()).await?;
-        match result.exit_status {
-            0 => {
-                monitor.parse_from_str(&result.stdout)?;
-                Ok(monitor)
+    /// 使用 ssh-agent（`SSH_AUTH_SOCK`）中的身份依次尝试认证
+    pub async fn with_agent(
+        user: impl AsRef<str>,
+        (host, port): (&str, u16),
+        check: HostKeyCheck,
+    ) -> Result<Self> {
+        let hop = HopConfig {
+            auth: Some(AuthKind::Agent),
+            ..HopConfig::direct(host, port, user.as_ref(), check)
+        };
+        Self::open(&hop, None).await
+    }
+
+    /// 按服务器配置建立连接，跳板链上的连接从 `pool` 中复用
+    pub async fn connect_from_config(config: &ServerConfig, pool: &JumpPool) -> Result<Self> {
+        let via = pool.connect_chain(&config.jump_chain).await?;
+        Self::open(&config.hop(), via).await
+    }
+
+    /// 底层 SSH 连接是否已经断开
+    pub fn is_closed(&self) -> bool {
+        self.handle.is_closed()
+    }
+
+    pub async fn disconnect(&self) -> Result<()> {
+        self.handle
+            .disconnect(russh::Disconnect::ByApplication, "", "")
+            .await?;
+        Ok(())
+    }
+
+    /// 在新的 session channel 上执行命令，收集 stdout/stderr 与退出码
+    pub async fn execute(&self, command: &str) -> Result<CommandOutput> {
+        let mut channel = self.handle.channel_open_session().await?;
+        channel.exec(true, command).await?;
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let mut exit_status = None;
+        while let Some(msg) = channel.wait().await {
+            match msg {
+                ChannelMsg::Data { ref data } => stdout.extend_from_slice(data),
+                ChannelMsg::ExtendedData { ref data, ext: 1 } => stderr.extend_from_slice(data),
+                // 退出码可能先于数据到达，不能据此提前结束
+                ChannelMsg::ExitStatus { exit_status: code } => exit_status = Some(code),
+                _ => {}
             }
-            code => Err(anyhow::anyhow!(
-                "command exited with non-zero status: {}",
-                code
-            )),
         }
+        let exit_status =
+            exit_status.ok_or_else(|| anyhow::anyhow!("command did not report an exit status"))?;
+        Ok(CommandOutput {
+            stdout: String::from_utf8_lossy(&stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&stderr).into_owned(),
+            exit_status,
+        })
+    }
+}
+
+#[async_trait]
+impl Transport for SSHClient {
+    async fn execute(&self, command: &str) -> Result<CommandOutput> {
+        SSHClient::execute(self, command).await
+    }
+
+    fn is_closed(&self) -> bool {
+        SSHClient::is_closed(self)
+    }
+
+    async fn disconnect(&self) -> Result<()> {
+        SSHClient::disconnect(self).await
     }
 }
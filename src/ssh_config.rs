@@ -0,0 +1,396 @@
+//! 读取 OpenSSH 客户端配置（`~/.ssh/config`），为 `ssh_alias` 解析 HostName、User、
+//! Port、IdentityFile 与 ProxyJump。
+//!
+//! 匹配规则与 OpenSSH 一致：按文件顺序检查每个 `Host` 块，模式支持 `*`、`?` 与 `!` 取反，
+//! 每个关键字取第一个匹配块给出的值。`Match` 块只支持 `Match all`，其余条件视为不匹配。
+
+use crate::config::{expand_home, split_host_port};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Include 的最大嵌套深度，与 OpenSSH 相同
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+#[derive(Debug, Default)]
+pub struct SshConfig {
+    blocks: Vec<Block>,
+}
+
+#[derive(Debug)]
+struct Block {
+    /// (是否取反, 模式)
+    patterns: Vec<(bool, String)>,
+    /// (小写关键字, 值)
+    options: Vec<(String, String)>,
+}
+
+impl Block {
+    fn all() -> Self {
+        Self {
+            patterns: vec![(false, "*".to_string())],
+            options: Vec::new(),
+        }
+    }
+
+    fn matches(&self, host: &str) -> bool {
+        let mut matched = false;
+        for (negated, pattern) in &self.patterns {
+            if wildcard_match(pattern, host) {
+                if *negated {
+                    return false;
+                }
+                matched = true;
+            }
+        }
+        matched
+    }
+}
+
+/// 某个别名解析出的连接参数
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SshHostConfig {
+    pub host_name: Option<String>,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    pub identity_file: Option<PathBuf>,
+    pub proxy_jump: Option<String>,
+}
+
+impl SshConfig {
+    /// 从文件加载；文件不存在时返回空配置
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read ssh config {}", path.display()))?;
+        Self::parse(&content)
+    }
+
+    /// 解析配置文本，`Include` 的相对路径以 `~/.ssh` 为基准
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut config = Self::default();
+        config.parse_into(content, 0, Block::all().patterns)?;
+        Ok(config)
+    }
+
+    fn read_file(
+        &mut self,
+        path: &Path,
+        depth: usize,
+        patterns: Vec<(bool, String)>,
+    ) -> Result<()> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read ssh config {}", path.display()))?;
+        self.parse_into(&content, depth, patterns)
+    }
+
+    /// `patterns` 是第一个 Host 行之前的选项所属的模式：顶层文件为 `*`，
+    /// 被 Include 的文件沿用 Include 所在块的模式
+    fn parse_into(
+        &mut self,
+        content: &str,
+        depth: usize,
+        patterns: Vec<(bool, String)>,
+    ) -> Result<()> {
+        self.blocks.push(Block {
+            patterns,
+            options: Vec::new(),
+        });
+        for line in content.lines() {
+            let Some((key, value)) = split_line(line) else {
+                continue;
+            };
+            match key.as_str() {
+                "host" => self.blocks.push(Block {
+                    patterns: split_args(&value)
+                        .into_iter()
+                        .map(|p| match p.strip_prefix('!') {
+                            Some(p) => (true, p.to_string()),
+                            None => (false, p),
+                        })
+                        .collect(),
+                    options: Vec::new(),
+                }),
+                "match" => {
+                    let all = split_args(&value)
+                        .first()
+                        .is_some_and(|c| c.eq_ignore_ascii_case("all"));
+                    self.blocks.push(Block {
+                        patterns: if all {
+                            vec![(false, "*".to_string())]
+                        } else {
+                            Vec::new()
+                        },
+                        options: Vec::new(),
+                    });
+                }
+                "include" => {
+                    if depth >= MAX_INCLUDE_DEPTH {
+                        anyhow::bail!("ssh config Include nested too deeply");
+                    }
+                    let outer = self
+                        .blocks
+                        .last()
+                        .map(|b| b.patterns.clone())
+                        .unwrap_or_default();
+                    for pattern in split_args(&value) {
+                        for path in include_paths(&pattern)? {
+                            self.read_file(&path, depth + 1, outer.clone())?;
+                        }
+                    }
+                    // Include 之后的选项仍属于外层当前块
+                    self.blocks.push(Block {
+                        patterns: outer,
+                        options: Vec::new(),
+                    });
+                }
+                _ => {
+                    if let Some(block) = self.blocks.last_mut() {
+                        block.options.push((key, value));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 每个关键字第一次出现的值
+    fn first(&self, host: &str, key: &str) -> Option<&str> {
+        self.blocks
+            .iter()
+            .filter(|b| b.matches(host))
+            .flat_map(|b| b.options.iter())
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// 按 OpenSSH 规则解析 `alias` 的连接参数
+    pub fn resolve(&self, alias: &str) -> SshHostConfig {
+        let host_name = self
+            .first(alias, "hostname")
+            .map(|h| h.replace("%h", alias).replace("%%", "%"));
+        let user = self.first(alias, "user").map(str::to_string);
+        let remote_host = host_name.clone().unwrap_or_else(|| alias.to_string());
+        let remote_user = user.clone().unwrap_or_else(local_user);
+        SshHostConfig {
+            port: self.first(alias, "port").and_then(|p| p.parse().ok()),
+            identity_file: self
+                .first(alias, "identityfile")
+                .map(|f| expand_tokens(f, &remote_host, &remote_user)),
+            proxy_jump: self
+                .first(alias, "proxyjump")
+                .filter(|j| !j.eq_ignore_ascii_case("none"))
+                .map(str::to_string),
+            host_name,
+            user,
+        }
+    }
+}
+
+/// 拆出关键字（小写）与值，支持 `Key value` 与 `Key=value`，忽略空行和注释
+fn split_line(line: &str) -> Option<(String, String)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let end = line.find(|c: char| c.is_whitespace() || c == '=')?;
+    let key = line[..end].to_ascii_lowercase();
+    let rest = line[end..].trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest).trim();
+    let value = rest
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(rest);
+    Some((key, value.to_string()))
+}
+
+/// 按空白拆分参数，双引号内的空白保留
+fn split_args(value: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in value.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+    args
+}
+
+/// OpenSSH 风格的通配：`*` 匹配任意串，`?` 匹配单个字符，大小写不敏感
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.to_lowercase().chars().collect();
+    let t: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// 展开 Include 参数，文件名部分允许通配
+fn include_paths(pattern: &str) -> Result<Vec<PathBuf>> {
+    let mut path = expand_home(Path::new(pattern));
+    if path.is_relative() {
+        path = expand_home(Path::new("~/.ssh")).join(path);
+    }
+    let file_name = path
+        .file_name()
+        .map(|f| f.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if !file_name.contains(['*', '?']) {
+        return Ok(if path.exists() {
+            vec![path]
+        } else {
+            Vec::new()
+        });
+    }
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut paths: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .filter(|e| wildcard_match(&file_name, &e.file_name().to_string_lossy()))
+            .map(|e| e.path())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+    Ok(paths)
+}
+
+/// 展开 IdentityFile 中的 `~` 与 `%d %h %r %u %%`
+fn expand_tokens(value: &str, host: &str, remote_user: &str) -> PathBuf {
+    let home = std::env::home_dir()
+        .map(|h| h.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut out = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('d') => out.push_str(&home),
+            Some('h') => out.push_str(host),
+            Some('r') => out.push_str(remote_user),
+            Some('u') => out.push_str(&local_user()),
+            Some('%') => out.push('%'),
+            Some(other) => {
+                out.push('%');
+                out.push(other);
+            }
+            None => out.push('%'),
+        }
+    }
+    expand_home(Path::new(&out))
+}
+
+/// 本地登录用户名，OpenSSH 在未配置 User 时使用它
+pub fn local_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default()
+}
+
+/// 用 ssh config 补全 `[[servers]]` 表中设置了 `ssh_alias` 的条目。
+///
+/// 只填充 TOML 里缺失的键，显式写出的值始终优先。
+pub fn apply_to_servers(table: &mut toml::Table, ssh: &SshConfig) -> Result<()> {
+    let Some(servers) = table.get_mut("servers").and_then(|s| s.as_array_mut()) else {
+        return Ok(());
+    };
+    for server in servers.iter_mut().filter_map(|s| s.as_table_mut()) {
+        let Some(alias) = server.get("ssh_alias").and_then(|a| a.as_str()) else {
+            continue;
+        };
+        let alias = alias.to_string();
+        let resolved = ssh.resolve(&alias);
+
+        let host = resolved.host_name.clone().unwrap_or_else(|| alias.clone());
+        server.entry("host").or_insert(host.into());
+        if let Some(user) = resolved
+            .user
+            .clone()
+            .or_else(|| Some(local_user()).filter(|u| !u.is_empty()))
+        {
+            server.entry("user").or_insert(user.into());
+        }
+        if let Some(port) = resolved.port {
+            server.entry("port").or_insert(i64::from(port).into());
+        }
+        if let Some(identity) = &resolved.identity_file {
+            server
+                .entry("privkey_path")
+                .or_insert(identity.to_string_lossy().into_owned().into());
+        }
+        if let Some(proxy_jump) = &resolved.proxy_jump
+            && !server.contains_key("jump")
+        {
+            let hops = proxy_jump
+                .split(',')
+                .map(|spec| jump_hop(spec.trim(), ssh))
+                .collect::<Result<Vec<_>>>()
+                .with_context(|| format!("invalid ProxyJump for ssh_alias {alias}"))?;
+            server.insert("jump".to_string(), toml::Value::Array(hops));
+        }
+    }
+    Ok(())
+}
+
+/// 把 ProxyJump 中的一项 `[user@]host[:port]` 解析成内联跳板表，host 同样按 ssh config 解析
+fn jump_hop(spec: &str, ssh: &SshConfig) -> Result<toml::Value> {
+    let (user, rest) = match spec.split_once('@') {
+        Some((user, rest)) => (Some(user.to_string()), rest),
+        None => (None, spec),
+    };
+    let (alias, port) = split_host_port(rest)?;
+    let resolved = ssh.resolve(alias);
+    let mut hop = toml::Table::new();
+    hop.insert(
+        "host".into(),
+        resolved
+            .host_name
+            .unwrap_or_else(|| alias.to_string())
+            .into(),
+    );
+    hop.insert(
+        "user".into(),
+        user.or(resolved.user).unwrap_or_else(local_user).into(),
+    );
+    if let Some(port) = port.or(resolved.port) {
+        hop.insert("port".into(), i64::from(port).into());
+    }
+    if let Some(identity) = resolved.identity_file {
+        hop.insert(
+            "privkey_path".into(),
+            identity.to_string_lossy().into_owned().into(),
+        );
+    }
+    Ok(toml::Value::Table(hop))
+}
@@ -0,0 +1,121 @@
+use crate::agent::{Backoff, Limiter, Schedule};
+use crate::config::{Config, ReconnectConfig};
+use crate::model::MonitorKind;
+use std::time::Duration;
+use tokio::time::Instant;
+
+fn reconnect(jitter: f64) -> ReconnectConfig {
+    ReconnectConfig {
+        initial_ms: 100,
+        max_ms: 1000,
+        multiplier: 2.0,
+        jitter,
+    }
+}
+
+#[test]
+fn test_backoff_grows_and_caps() {
+    let mut backoff = Backoff::new(reconnect(0.0));
+    let delays: Vec<u128> = (0..6).map(|_| backoff.next_delay().as_millis()).collect();
+    assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+
+    backoff.reset();
+    assert_eq!(backoff.next_delay().as_millis(), 100);
+}
+
+#[test]
+fn test_backoff_jitter_within_bounds() {
+    let mut backoff = Backoff::new(reconnect(0.5));
+    for _ in 0..50 {
+        backoff.reset();
+        let d = backoff.next_delay().as_millis();
+        assert!((50..=150).contains(&d), "delay {d} out of range");
+    }
+
+    // 到达上限后抖动只能向下
+    for _ in 0..50 {
+        let d = backoff.next_delay().as_millis();
+        assert!(d <= 1000, "delay {d} exceeds max_ms");
+    }
+}
+
+#[test]
+fn test_schedule_per_monitor_intervals() {
+    let config: Config = toml::from_str(
+        r#"
+        [global]
+        [[servers]]
+        name = "local"
+        transport = "local"
+        monitors = ["Cpu", "Disk"]
+        poll_interval_ms = 1000
+        monitor_interval_ms = { Disk = 60000 }
+        "#,
+    )
+    .unwrap();
+    config.validate().unwrap();
+    let start = Instant::now();
+    let secs = |s: u64| start + Duration::from_secs(s);
+    let mut schedule = Schedule::new(&config.servers[0], start);
+
+    assert_eq!(
+        schedule.due(start),
+        vec![MonitorKind::Cpu, MonitorKind::Disk]
+    );
+    schedule.advance(&[MonitorKind::Cpu, MonitorKind::Disk], start);
+    assert_eq!(schedule.next_deadline(), Some(secs(1)));
+    assert!(schedule.due(start).is_empty());
+
+    for s in 1..60 {
+        assert_eq!(schedule.due(secs(s)), vec![MonitorKind::Cpu]);
+        schedule.advance(&[MonitorKind::Cpu], secs(s));
+    }
+    assert_eq!(
+        schedule.due(secs(60)),
+        vec![MonitorKind::Cpu, MonitorKind::Disk]
+    );
+
+    // 落后多个周期时不补跑，从当前时刻重新计时
+    schedule.advance(&[MonitorKind::Cpu, MonitorKind::Disk], secs(65));
+    assert_eq!(schedule.next_deadline(), Some(secs(66)));
+}
+
+#[test]
+fn test_monitor_interval_must_match_monitors() {
+    let config: Config = toml::from_str(
+        r#"
+        [global]
+        [[servers]]
+        name = "local"
+        transport = "local"
+        monitors = ["Cpu"]
+        monitor_interval_ms = { Disk = 60000 }
+        "#,
+    )
+    .unwrap();
+    assert!(config.validate().is_err());
+}
+
+#[tokio::test]
+async fn test_limiter_counts_queue() {
+    let limiter = Limiter::new(1);
+    let permit = limiter.acquire().await;
+    assert_eq!((limiter.queued(), limiter.running()), (0, 1));
+
+    let waiter = {
+        let limiter = limiter.clone();
+        tokio::spawn(async move {
+            let _permit = limiter.acquire().await;
+        })
+    };
+    tokio::task::yield_now().await;
+    assert_eq!(limiter.queued(), 1);
+
+    // 排队中的任务被取消后不再计入
+    waiter.abort();
+    let _ = waiter.await;
+    assert_eq!(limiter.queued(), 0);
+
+    drop(permit);
+    assert_eq!(limiter.running(), 0);
+}
@@ -0,0 +1,246 @@
+use crate::config::{AuthKind, Config};
+use crate::model::MonitorKind;
+use std::time::Duration;
+
+fn parse(s: &str) -> anyhow::Result<Config> {
+    let mut config: Config = toml::from_str(s)?;
+    config.resolve_jumps()?;
+    config.resolve_custom_monitors();
+    config.validate()?;
+    Ok(config)
+}
+
+#[test]
+fn test_jump_chain_resolution() {
+    let config = parse(
+        r#"
+        [global]
+        [[servers]]
+        name = "edge"
+        host = "203.0.113.1"
+        user = "ops"
+        auth = "agent"
+        monitors = ["Mem"]
+
+        [[servers]]
+        name = "bastion"
+        host = "10.0.0.1"
+        user = "jump"
+        privkey_path = "/keys/jump"
+        jump = ["edge"]
+        monitors = ["Mem"]
+
+        [[servers]]
+        name = "db"
+        host = "10.1.0.5"
+        user = "root"
+        privkey_path = "/keys/db"
+        jump = ["bastion", "admin@10.1.0.1:2222", { host = "10.1.0.2", user = "x", password = "p" }]
+        monitors = ["Mem"]
+        "#,
+    )
+    .unwrap();
+
+    let db = &config.servers[2];
+    let labels: Vec<String> = db.jump_chain.iter().map(|h| h.label()).collect();
+    assert_eq!(
+        labels,
+        vec![
+            "ops@203.0.113.1:22",
+            "jump@10.0.0.1:22",
+            "admin@10.1.0.1:2222",
+            "x@10.1.0.2:22",
+        ]
+    );
+    assert_eq!(db.jump_chain[0].auth_kind(), AuthKind::Agent);
+    // 以 user@host 形式给出的跳板沿用目标服务器的密钥
+    assert_eq!(db.jump_chain[2].privkey_path, db.privkey_path);
+    assert_eq!(db.jump_chain[3].auth_kind(), AuthKind::Password);
+    assert!(config.servers[0].jump_chain.is_empty());
+}
+
+#[test]
+fn test_jump_spec_ipv6() {
+    let config = parse(
+        r#"
+        [global]
+        [[servers]]
+        name = "db"
+        host = "2001:db8:1::5"
+        user = "root"
+        privkey_path = "/keys/db"
+        jump = ["ops@2001:db8::1", "[2001:db8::2]:2222", "admin@[fe80::1%eth0]", "10.0.0.1:2200"]
+        monitors = ["Mem"]
+        "#,
+    )
+    .unwrap();
+    let hops: Vec<(&str, &str, u16)> = config.servers[0]
+        .jump_chain
+        .iter()
+        .map(|h| (h.user.as_str(), h.host.as_str(), h.port))
+        .collect();
+    assert_eq!(
+        hops,
+        vec![
+            ("ops", "2001:db8::1", 22),
+            ("root", "2001:db8::2", 2222),
+            ("admin", "fe80::1%eth0", 22),
+            ("root", "10.0.0.1", 2200),
+        ]
+    );
+
+    for bad in ["[2001:db8::2", "[2001:db8::2]2222", "[2001:db8::2]:ssh"] {
+        let err = parse(&format!(
+            r#"
+            [global]
+            [[servers]]
+            name = "db"
+            host = "10.1.0.5"
+            user = "root"
+            jump = ["{bad}"]
+            monitors = ["Mem"]
+            "#
+        ))
+        .unwrap_err();
+        assert!(err.to_string().contains("jump host"), "{bad}: {err}");
+    }
+}
+
+#[test]
+fn test_jump_chain_cycle_rejected() {
+    let err = parse(
+        r#"
+        [global]
+        [[servers]]
+        name = "a"
+        host = "10.0.0.1"
+        user = "root"
+        jump = ["b"]
+        monitors = ["Mem"]
+
+        [[servers]]
+        name = "b"
+        host = "10.0.0.2"
+        user = "root"
+        jump = ["a"]
+        monitors = ["Mem"]
+        "#,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("loops back"));
+}
+
+#[test]
+fn test_custom_monitor_references() {
+    let base = r#"
+        [global]
+        [[servers]]
+        name = "app"
+        transport = "local"
+        monitors = [{ Custom = "queue" }]
+
+        [[custom_monitors]]
+        name = "queue"
+        command = "cat /var/run/queue_depth"
+        parser = "Number"
+        interval_ms = 5000
+        "#;
+    let config = parse(base).unwrap();
+    let server = &config.servers[0];
+    assert_eq!(server.monitor_opts.custom.len(), 1);
+    assert_eq!(
+        server.interval_of(&MonitorKind::Custom("queue".to_string())),
+        Duration::from_secs(5)
+    );
+
+    let err = parse(&base.replace(r#"Custom = "queue" }]"#, r#"Custom = "jobs" }]"#)).unwrap_err();
+    assert!(err.to_string().contains("custom monitor jobs"), "{err}");
+
+    let err =
+        parse(&base.replace(r#"parser = "Number""#, r#"parser = { Regex = '\d+' }"#)).unwrap_err();
+    assert!(err.to_string().contains("no named capture groups"), "{err}");
+
+    let err = parse(&format!(
+        "{base}\n[[custom_monitors]]\nname = \"queue\"\ncommand = \"true\"\nparser = \"Number\"\n"
+    ))
+    .unwrap_err();
+    assert!(err.to_string().contains("defined more than once"), "{err}");
+}
+
+#[tokio::test]
+async fn test_plugin_loading() {
+    let base = format!(
+        r#"
+        [global]
+        [[servers]]
+        name = "cache"
+        transport = "local"
+        monitors = [{{ Plugin = "redis" }}]
+
+        [[plugins]]
+        path = "{}/src/tests/fixtures/plugins/redis.sh"
+        options = {{ port = 6380 }}
+        "#,
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let mut config: Config = toml::from_str(&base).unwrap();
+    config.resolve_plugins().await.unwrap();
+    config.validate().unwrap();
+    let server = &config.servers[0];
+    let plugin = server.monitor_opts.plugin("redis").unwrap();
+    assert_eq!(plugin.label, "Redis");
+    // 选项覆盖默认值，并经引号转义后代入命令
+    assert_eq!(plugin.command, "redis-cli -p '6380' info stats");
+    assert_eq!(
+        server.interval_of(&MonitorKind::Plugin("redis".to_string())),
+        Duration::from_secs(2)
+    );
+
+    let mut config: Config = toml::from_str(&base.replace("port = 6380", "prot = 6380")).unwrap();
+    let err = config.resolve_plugins().await.unwrap_err();
+    assert!(err.to_string().contains("no option \"prot\""), "{err}");
+
+    let mut config: Config =
+        toml::from_str(&base.replace(r#"Plugin = "redis""#, r#"Plugin = "kafka""#)).unwrap();
+    config.resolve_plugins().await.unwrap();
+    let err = config.validate().unwrap_err();
+    assert!(err.to_string().contains("plugin kafka"), "{err}");
+}
+
+#[tokio::test]
+async fn test_plugin_describe_timeout() {
+    let mut config: Config = toml::from_str(&format!(
+        r#"
+        servers = []
+        [global]
+        [[plugins]]
+        path = "{}/src/tests/fixtures/plugins/hang.sh"
+        timeout_ms = 200
+        "#,
+        env!("CARGO_MANIFEST_DIR")
+    ))
+    .unwrap();
+    let started = std::time::Instant::now();
+    let err = config.resolve_plugins().await.unwrap_err();
+    assert!(format!("{err:#}").contains("describe timed out"), "{err:#}");
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_process_monitor_requires_watch_list() {
+    let base = r#"
+        [global]
+        [[servers]]
+        name = "app"
+        transport = "local"
+        monitors = ["Process"]
+        "#;
+    let err = parse(base).unwrap_err();
+    assert!(err.to_string().contains("watches nothing"), "{err}");
+
+    let config = parse(&format!(
+        "{base}\n[servers.monitor_opts.process]\nwatch = [{{ Name = \"nginx\" }}]\n"
+    ))
+    .unwrap();
+    assert_eq!(config.servers[0].monitor_opts.process.watch.len(), 1);
+}
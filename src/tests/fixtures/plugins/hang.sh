@@ -0,0 +1,3 @@
+#!/bin/sh
+# 测试用插件：describe 卡住不退出，用于检验加载时的超时
+exec sleep 10
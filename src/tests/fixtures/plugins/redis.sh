@@ -0,0 +1,20 @@
+#!/bin/sh
+# 测试用插件：把 redis-cli info 的输出转换为 stalking 的指标
+case "$1" in
+describe)
+    echo '{"name": "redis", "label": "Redis", "command": "redis-cli -p {port} info stats", "interval_ms": 2000, "options": {"port": 6379}}'
+    ;;
+parse)
+    tr -d '\r' | awk -F: '
+        /^instantaneous_ops_per_sec:/ { ops = $2 }
+        /^role:/ { role = $2 }
+        END {
+            if (ops == "") { print "no stats in input" > "/dev/stderr"; exit 1 }
+            printf "{\"metrics\": [{\"key\": \"ops\", \"value\": %s, \"unit\": \"/s\"}, {\"key\": \"role\", \"value\": \"%s\"}]}\n", ops, role
+        }'
+    ;;
+*)
+    echo "usage: $0 describe|parse" >&2
+    exit 2
+    ;;
+esac
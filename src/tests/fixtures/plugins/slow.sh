@@ -0,0 +1,10 @@
+#!/bin/sh
+# 测试用插件：describe 正常返回，parse 卡住不退出，用于检验超时
+case "$1" in
+describe)
+    echo '{"name": "slow", "command": "redis-cli info stats"}'
+    ;;
+*)
+    exec sleep 10
+    ;;
+esac
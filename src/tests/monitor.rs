@@ -0,0 +1,806 @@
+use crate::config::Config;
+use crate::model::Alert;
+use crate::monitor::sensors::{Fan, Temperature, sensors_cmd};
+use crate::monitor::{
+    CpuInfo, CustomInfo, CustomMonitor, DiskInfo, DiskIoInfo, LoadInfo, MemInfo, MonitorExecConfig,
+    Monitorable, NetInfo, PressureInfo, ProcessWatchInfo, RemoteOs, SensorsInfo, SocketsInfo,
+    SystemdInfo, ToolCaps, parse_probe,
+};
+
+const NETDEV_HEADER: &str = "Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+";
+
+fn net_sample(uptime: &str, rx: u64, tx: u64) -> NetInfo {
+    let mut info = NetInfo::default();
+    info.parse_from_str(
+        &format!("{uptime} 0.00\n{NETDEV_HEADER}  eth0: {rx} 0 0 0 0 0 0 0 {tx} 0 0 0 0 0 0 0\n"),
+        &MonitorExecConfig::default(),
+    )
+    .unwrap();
+    info
+}
+
+fn cpu_sample(uptime: &str, busy: u64, idle: u64) -> CpuInfo {
+    let mut info = CpuInfo::default();
+    info.parse_from_str(
+        &format!(
+            "{uptime} 0.00\ncpu  {busy} 0 0 {idle} 0 0 0 0 0 0\n---\n    PID COMMAND %CPU %MEM\n"
+        ),
+        &MonitorExecConfig::default(),
+    )
+    .unwrap();
+    info
+}
+
+#[test]
+fn test_net_rate_uses_remote_elapsed_time() {
+    let first = net_sample("100.00", 1_000_000, 500_000);
+    // 没有上一次采样时给出开机以来的平均速率
+    assert_eq!(first.interfaces[0].rx_rate, 10_000.0);
+
+    // 两次采样实际相隔 2.5s，与轮询间隔无关
+    let mut second = net_sample("102.50", 1_256_000, 500_000);
+    second.apply_previous(&first);
+    assert_eq!(second.interfaces[0].rx_rate, 102_400.0);
+    assert_eq!(second.interfaces[0].tx_rate, 0.0);
+}
+
+#[test]
+fn test_cpu_usage_between_samples() {
+    let first = cpu_sample("100.00", 1000, 9000);
+    assert_eq!(first.usage_percent, 10.0);
+
+    let mut second = cpu_sample("101.00", 1075, 9025);
+    second.apply_previous(&first);
+    assert_eq!(second.usage_percent, 75.0);
+
+    // 远端重启后计数器变小，不能与旧采样求差
+    let mut rebooted = cpu_sample("5.00", 50, 450);
+    rebooted.apply_previous(&second);
+    assert_eq!(rebooted.usage_percent, 10.0);
+}
+
+#[test]
+fn test_cpu_breakdown_per_core() {
+    let sample = |uptime: &str, stat: &str| {
+        let mut info = CpuInfo::default();
+        info.parse_from_str(
+            &format!("{uptime} 0.00\n{stat}---\n    PID COMMAND %CPU %MEM\n"),
+            &MonitorExecConfig::default(),
+        )
+        .unwrap();
+        info
+    };
+    // user nice system idle iowait irq softirq steal guest guest_nice
+    let first = sample(
+        "100.00",
+        "cpu  100 0 100 700 0 0 0 100 50 0\ncpu0 50 0 50 350 0 0 0 50 0 0\n\
+         cpu1 50 0 50 350 0 0 0 50 0 0\nintr 5000 1 2\nctxt 10000\n",
+    );
+    assert_eq!(first.breakdown.steal, 10.0);
+    assert_eq!(first.ctxt_rate, Some(100.0));
+
+    let mut second = sample(
+        "102.00",
+        "cpu  140 10 120 720 40 10 10 150 70 0\ncpu0 90 10 70 350 0 0 10 100 0 0\n\
+         cpu1 50 0 50 370 40 10 0 50 0 0\nintr 5400 1 2\nctxt 10600\n",
+    );
+    second.apply_previous(&first);
+    // 两次之间共 200 jiffies：user 40、nice 10、system 20、idle 20、iowait 40……
+    let b = second.breakdown;
+    assert_eq!((b.user, b.nice, b.system, b.idle), (20.0, 5.0, 10.0, 10.0));
+    assert_eq!(
+        (b.iowait, b.irq, b.softirq, b.steal),
+        (20.0, 5.0, 5.0, 25.0)
+    );
+    assert_eq!(b.guest, 10.0);
+    assert_eq!(second.usage_percent, 70.0);
+    // cpu0 全忙，cpu1 大部分时间空闲或等待 I/O
+    assert_eq!(second.core_usage[0], 100.0);
+    assert!((second.core_usage[1] - 100.0 / 7.0).abs() < 1e-9);
+    assert_eq!(second.core_strip(), "█▁");
+    assert_eq!(second.ctxt_rate, Some(300.0));
+    assert_eq!(second.intr_rate, Some(200.0));
+
+    // 较老的内核只有 7 列
+    let old = sample("10.00", "cpu  10 0 10 80 0 0 0\n");
+    assert_eq!(old.usage_percent, 20.0);
+    assert_eq!(old.breakdown.steal, 0.0);
+}
+
+fn diskio_sample(uptime: &str, sda: &str, config: &MonitorExecConfig) -> DiskIoInfo {
+    let mut info = DiskIoInfo::default();
+    info.parse_from_str(
+        &format!(
+            "{uptime} 0.00\n   7       0 loop0 50 0 100 5 0 0 0 0 0 10 5 0 0 0 0\n\
+             8       0 sda {sda} 0 0 0 0\n   8       1 sda1 900 0 18000 450 400 0 8000 900 0 1800 2700 0 0 0 0\n\
+             ---\nloop0\nsda\n"
+        ),
+        config,
+    )
+    .unwrap();
+    info
+}
+
+#[test]
+fn test_diskio_rates_between_samples() -> anyhow::Result<()> {
+    let defaults = MonitorExecConfig::default();
+    // reads merged sectors ms writes merged sectors ms in_flight io_ms weighted_ms
+    let first = diskio_sample(
+        "100.00",
+        "1000 0 20000 500 500 0 10000 1000 0 2000 3000",
+        &defaults,
+    );
+    // 默认不显示分区与 loop 设备
+    assert_eq!(first.devices.len(), 1);
+    let sda = &first.devices[0];
+    assert_eq!(sda.name, "sda");
+    // 首次采样给出开机以来的平均值
+    assert_eq!((sda.reads_per_sec, sda.writes_per_sec), (10.0, 5.0));
+    assert_eq!(sda.read_bytes_per_sec, 102_400.0);
+    assert_eq!(sda.await_ms, 1.0);
+    assert_eq!(sda.util_percent, 2.0);
+
+    let mut second = diskio_sample(
+        "102.00",
+        "1200 0 24096 900 700 0 12048 1800 2 3800 6000",
+        &defaults,
+    );
+    second.apply_previous(&first);
+    let sda = &second.devices[0];
+    assert_eq!((sda.reads_per_sec, sda.writes_per_sec), (100.0, 100.0));
+    assert_eq!(sda.read_bytes_per_sec, 1_048_576.0);
+    assert_eq!(sda.write_bytes_per_sec, 524_288.0);
+    // 400 次 I/O 共耗时 1200ms
+    assert_eq!(sda.await_ms, 3.0);
+    assert_eq!(sda.util_percent, 90.0);
+    assert_eq!(sda.queue_depth, 1.5);
+    assert_eq!(sda.counters.in_flight, 2);
+
+    let config = exec_config(
+        "[servers.monitor_opts.diskio]\ninclude_partitions = true\ninclude_loop = true\nexclude = [\"sda\"]",
+    )?;
+    let all = diskio_sample(
+        "100.00",
+        "1000 0 20000 500 500 0 10000 1000 0 2000 3000",
+        &config,
+    );
+    let names: Vec<&str> = all.devices.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, ["loop0", "sda1"]);
+
+    assert!(!DiskIoInfo::default().supported(RemoteOs::FreeBsd));
+    Ok(())
+}
+
+#[test]
+fn test_pressure_without_psi() -> anyhow::Result<()> {
+    // 4.20 以前的内核没有 /proc/pressure，4.13 以前没有 oom_kill
+    let mut first = PressureInfo::default();
+    first.parse_from_str(
+        "50.00 90.00\n---\npswpin 100\npswpout 0\npgmajfault 500\n",
+        &MonitorExecConfig::default(),
+    )?;
+    assert!(first.memory.is_none());
+    assert_eq!(first.oom_kill, None);
+    assert_eq!(first.swap_in_rate, 2.0);
+    assert_eq!(first.major_fault_rate, 10.0);
+    assert!(
+        first
+            .common_display()
+            .contains("Pressure stall information not available")
+    );
+
+    let mut second = PressureInfo::default();
+    second.parse_from_str(
+        "54.00 98.00\n---\npswpin 140\npswpout 0\npgmajfault 520\n",
+        &MonitorExecConfig::default(),
+    )?;
+    second.apply_previous(&first);
+    assert_eq!(second.swap_in_rate, 10.0);
+    assert_eq!(second.major_fault_rate, 5.0);
+    assert!(second.alerts(&first).is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_process_watch_command_and_validation() -> anyhow::Result<()> {
+    let config = exec_config(
+        r#"
+        [servers.monitor_opts.process]
+        watch = [{ Name = "nginx" }, { Regex = "it's.*up" }, { Pidfile = "/run/app.pid" }]
+        "#,
+    )?;
+    let cmd = ProcessWatchInfo::default().exec_cmd(&config);
+    assert!(
+        cmd.contains("echo '--- 0'; __stalking_proc $(pgrep -x 'nginx')"),
+        "{cmd}"
+    );
+    // 单引号经过转义，正则原样交给 pgrep
+    assert!(cmd.contains(r"$(pgrep -f 'it'\''s.*up')"), "{cmd}");
+    assert!(
+        cmd.contains("$(head -n 1 '/run/app.pid' 2>/dev/null)"),
+        "{cmd}"
+    );
+
+    for (watch, expected) in [
+        (r#"[{ Regex = "(unclosed" }]"#, "invalid process regex"),
+        (r#"[{ Pidfile = "run/app.pid" }]"#, "absolute path"),
+        (r#"[{ Name = "" }]"#, "invalid process name"),
+    ] {
+        let err =
+            exec_config(&format!("[servers.monitor_opts.process]\nwatch = {watch}")).unwrap_err();
+        assert!(err.to_string().contains(expected), "{err}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_systemd_command() -> anyhow::Result<()> {
+    let failed_only = SystemdInfo::default().exec_cmd(&exec_config("")?);
+    assert!(
+        failed_only.contains("set -- $(systemctl list-units --state=failed"),
+        "{failed_only}"
+    );
+
+    let config = exec_config(
+        r#"
+        [servers.monitor_opts.systemd]
+        units = ["nginx", "getty@tty1.service"]
+        include_failed = false
+        "#,
+    )?;
+    let cmd = SystemdInfo::default().exec_cmd(&config);
+    assert!(
+        cmd.contains("set -- 'nginx' 'getty@tty1.service';"),
+        "{cmd}"
+    );
+    assert!(!cmd.contains("list-units"), "{cmd}");
+
+    let mut info = SystemdInfo::default();
+    let err = info
+        .parse_from_str("10.00 20.00\nno-systemctl\n", &config)
+        .unwrap_err();
+    assert!(err.to_string().contains("systemctl not found"), "{err}");
+    Ok(())
+}
+
+#[test]
+fn test_sockets_without_ss() -> anyhow::Result<()> {
+    // BusyBox 没有 ss：只有计数器，也不产生端口事件
+    let snmp = "Tcp: ActiveOpens OutSegs RetransSegs\nTcp: 10 5000 50\n";
+    let mut first = SocketsInfo::default();
+    first.parse_from_str(
+        &format!("50.00 90.00\n{snmp}---\n---\n"),
+        &MonitorExecConfig::default(),
+    )?;
+    assert_eq!(first.tcp_states, None);
+    assert_eq!(first.listening, None);
+    assert_eq!(first.retrans_rate, 1.0);
+    assert_eq!(first.retrans_percent, 1.0);
+    assert!(
+        first
+            .common_display()
+            .contains("TCP states not available (ss not found)")
+    );
+
+    let mut second = first.clone();
+    second.parse_from_str(
+        "51.00 92.00\nTcp: ActiveOpens OutSegs RetransSegs\nTcp: 10 5100 60\n---\n---\n",
+        &MonitorExecConfig::default(),
+    )?;
+    second.apply_previous(&first);
+    assert_eq!(second.retrans_rate, 10.0);
+    assert_eq!(second.retrans_percent, 10.0);
+    assert!(second.alerts(&first).is_empty());
+    Ok(())
+}
+
+/// 在本机对采集下来的 sysfs 目录树执行与远端相同的命令
+fn sensors_from_tree(tree: &str) -> anyhow::Result<SensorsInfo> {
+    let class_dir = format!(
+        "{}/src/tests/fixtures/sysfs/{tree}/class",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let output = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(sensors_cmd(&class_dir))
+        .output()?;
+    let mut info = SensorsInfo::default();
+    info.parse_from_str(
+        &String::from_utf8(output.stdout)?,
+        &MonitorExecConfig::default(),
+    )?;
+    Ok(info)
+}
+
+#[test]
+fn test_sensors_from_sysfs_tree() -> anyhow::Result<()> {
+    let info = sensors_from_tree("server")?;
+    let temps: Vec<(&str, &str, f64)> = info
+        .temperatures
+        .iter()
+        .map(|t| (t.chip.as_str(), t.label.as_str(), t.celsius))
+        .collect();
+    // hwmon10 排在 hwmon2 之后，temp10 排在 temp2 之后；没有 label 文件时用文件名
+    assert_eq!(
+        temps,
+        vec![
+            ("acpitz", "temp1", 27.8),
+            ("coretemp", "Package id 0", 81.0),
+            ("coretemp", "Core 0", 52.0),
+            ("coretemp", "Core 8", 49.0),
+            ("nct6775", "SYSTIN", 34.0),
+            ("nvme", "Composite", 41.85),
+            ("thermal_zone0", "acpitz", 27.8),
+            ("thermal_zone1", "x86_pkg_temp", 81.0),
+        ]
+    );
+    let package = &info.temperatures[1];
+    assert_eq!((package.high, package.critical), (Some(80.0), Some(100.0)));
+    // 值为 0 的触发点视为未设置
+    let pkg_zone = &info.temperatures[7];
+    assert_eq!((pkg_zone.high, pkg_zone.critical), (Some(95.0), None));
+    assert_eq!(info.temperatures[6].critical, Some(119.0));
+
+    let fans: Vec<(&str, u32)> = info
+        .fans
+        .iter()
+        .map(|f| (f.label.as_str(), f.rpm))
+        .collect();
+    assert_eq!(fans, vec![("fan1", 0), ("CPU Fan", 1215)]);
+
+    let display = info.common_display();
+    assert!(
+        display.contains(
+            "coretemp       Package id 0       81.0°C  (high 80.0°C, crit 100.0°C)  HOT\n"
+        ),
+        "{display}"
+    );
+    assert!(
+        display.contains("  nct6775        CPU Fan            1215 RPM\n"),
+        "{display}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_sensors_from_legacy_device_dirs() -> anyhow::Result<()> {
+    // 属性在 hwmonN/device/ 下；hwmon1 的 name 两处都有，取 hwmon1/name
+    let info = sensors_from_tree("legacy")?;
+    assert_eq!(
+        info.temperatures,
+        vec![
+            Temperature {
+                chip: "w83627ehf".to_string(),
+                label: "SYSTIN".to_string(),
+                celsius: 38.0,
+                high: Some(75.0),
+                critical: None,
+            },
+            Temperature {
+                chip: "k10temp".to_string(),
+                label: "temp1".to_string(),
+                celsius: 45.5,
+                high: None,
+                critical: Some(90.0),
+            },
+        ]
+    );
+    assert_eq!(
+        info.fans,
+        vec![Fan {
+            chip: "w83627ehf".to_string(),
+            label: "CPU Fan".to_string(),
+            rpm: 1500,
+        }]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_sensors_on_vm_without_hwmon() -> anyhow::Result<()> {
+    let info = sensors_from_tree("vm")?;
+    assert_eq!(info, SensorsInfo::default());
+    assert_eq!(
+        info.common_display(),
+        "No hardware sensors found (virtual machine?)\n"
+    );
+    // 完全没有 /sys/class/thermal 也不报错
+    assert_eq!(sensors_from_tree("missing")?, SensorsInfo::default());
+    Ok(())
+}
+
+fn exec_config(opts: &str) -> anyhow::Result<MonitorExecConfig> {
+    let config: Config = toml::from_str(&format!(
+        r#"
+        [global]
+        [[servers]]
+        name = "local"
+        transport = "local"
+        monitors = ["Cpu", "Disk", "Net"]
+        {opts}
+        "#
+    ))?;
+    config.validate()?;
+    Ok(MonitorExecConfig::new(&config.servers[0]))
+}
+
+#[test]
+fn test_monitor_opts_shape_commands() -> anyhow::Result<()> {
+    let defaults = exec_config("")?;
+    assert_eq!(
+        DiskInfo::default().exec_cmd(&defaults),
+        "df -P -x tmpfs -x devtmpfs -x devfs -x autofs; echo '---'; \
+         df -Pi -x tmpfs -x devtmpfs -x devfs -x autofs"
+    );
+    assert!(
+        CpuInfo::default()
+            .exec_cmd(&defaults)
+            .ends_with("head -n 11")
+    );
+
+    let config = exec_config(
+        r#"
+        [servers.monitor_opts.disk]
+        exclude_fs = ["tmpfs", "overlay", "squashfs"]
+        [servers.monitor_opts.cpu]
+        top_n = 2
+        [servers.monitor_opts.net]
+        exclude = ["lo"]
+        "#,
+    )?;
+    assert_eq!(
+        DiskInfo::default().exec_cmd(&config),
+        "df -P -x tmpfs -x overlay -x squashfs; echo '---'; df -Pi -x tmpfs -x overlay -x squashfs"
+    );
+    assert!(CpuInfo::default().exec_cmd(&config).ends_with("head -n 3"));
+
+    let mut cpu = CpuInfo::default();
+    cpu.parse_from_str(
+        "1.00 0.00\ncpu  1 0 0 1 0 0 0\n---\n  PID COMMAND %CPU %MEM\n 1 a 3.0 0.1\n 2 b 2.0 0.1\n 3 c 1.0 0.1\n",
+        &config,
+    )?;
+    assert_eq!(cpu.top_processes.len(), 2);
+
+    let mut net = NetInfo::default();
+    net.parse_from_str(
+        &format!(
+            "1.00 0.00\n{NETDEV_HEADER}    lo: 1 0 0 0 0 0 0 0 1 0 0 0 0 0 0 0\n  eth0: 2 0 0 0 0 0 0 0 2 0 0 0 0 0 0 0\n"
+        ),
+        &config,
+    )?;
+    let names: Vec<&str> = net.interfaces.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(names, vec!["eth0"]);
+    Ok(())
+}
+
+#[test]
+fn test_monitor_opts_reject_shell_metacharacters() {
+    let err = exec_config(
+        r#"
+        [servers.monitor_opts.disk]
+        exclude_fs = ["tmpfs; rm -rf /"]
+        "#,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("exclude_fs"), "{err}");
+}
+
+fn freebsd() -> MonitorExecConfig {
+    MonitorExecConfig {
+        os: RemoteOs::FreeBsd,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_macos_cpu_sampling_runs_in_background() -> anyhow::Result<()> {
+    // 用假的 top 在本机执行 macOS 的命令：批量命令不等待 1 秒的采样间隔
+    let fixtures = format!("{}/src/tests/fixtures/darwin", env!("CARGO_MANIFEST_DIR"));
+    let tmpdir = std::env::temp_dir().join(format!("stalking-macos-cpu-{}", std::process::id()));
+    std::fs::create_dir_all(&tmpdir)?;
+    let config = MonitorExecConfig {
+        os: RemoteOs::MacOs,
+        ..Default::default()
+    };
+    let cmd = CpuInfo::default().exec_cmd(&config);
+    let run = || -> anyhow::Result<(CpuInfo, std::time::Duration)> {
+        let started = std::time::Instant::now();
+        let output = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(&cmd)
+            .env("PATH", format!("{fixtures}/bin:{}", std::env::var("PATH")?))
+            .env("TMPDIR", &tmpdir)
+            .output()?;
+        let elapsed = started.elapsed();
+        let mut info = CpuInfo::default();
+        info.parse_from_str(&String::from_utf8(output.stdout)?, &config)?;
+        Ok((info, elapsed))
+    };
+
+    // 首轮还没有后台结果，退回开机以来的平均值
+    let (first, elapsed) = run()?;
+    assert!(
+        elapsed < std::time::Duration::from_millis(900),
+        "{elapsed:?}"
+    );
+    assert!((first.usage_percent - 5.15).abs() < 1e-9);
+    std::thread::sleep(std::time::Duration::from_millis(1500));
+    let (second, elapsed) = run()?;
+    assert!(
+        elapsed < std::time::Duration::from_millis(900),
+        "{elapsed:?}"
+    );
+    assert_eq!(second.usage_percent, 18.75);
+    std::fs::remove_dir_all(&tmpdir)?;
+    Ok(())
+}
+
+#[test]
+fn test_remote_os_detection() {
+    assert_eq!(RemoteOs::from_uname("Linux\n").unwrap(), RemoteOs::Linux);
+    assert_eq!(RemoteOs::from_uname("Darwin\n").unwrap(), RemoteOs::MacOs);
+    assert_eq!(
+        RemoteOs::from_uname("FreeBSD\n").unwrap(),
+        RemoteOs::FreeBsd
+    );
+    assert!(RemoteOs::from_uname("SunOS\n").is_err());
+}
+
+#[test]
+fn test_probe_detects_busybox() -> anyhow::Result<()> {
+    let (os, caps) = parse_probe("Linux\ngnu-ps\ngnu-df\n")?;
+    assert_eq!(os, RemoteOs::Linux);
+    assert_eq!(caps, ToolCaps::default());
+
+    let (_, caps) = parse_probe("Linux\n")?;
+    assert!(!caps.gnu_ps && !caps.gnu_df);
+    let config = MonitorExecConfig {
+        caps,
+        ..MonitorExecConfig::default()
+    };
+    assert!(!CpuInfo::default().exec_cmd(&config).contains("ps "));
+    assert_eq!(
+        DiskInfo::default().exec_cmd(&config),
+        "df -P; echo '---'; df -Pi 2>/dev/null; echo '---'; cat /proc/mounts"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_probe_reads_page_size_and_clock_ticks() -> anyhow::Result<()> {
+    // arm64 上常见 64 KiB 页，部分内核 CLK_TCK 不是 100
+    let (_, caps) = parse_probe("Linux\ngnu-ps\ngnu-df\nsysconf 65536 250\n")?;
+    assert_eq!((caps.page_kb, caps.clk_tck), (64, 250));
+    // 没有 getconf 时保留默认值
+    let (_, caps) = parse_probe("Linux\nsysconf\n")?;
+    assert_eq!((caps.page_kb, caps.clk_tck), (4, 100));
+
+    let mut config = exec_config("[servers.monitor_opts.process]\nwatch = [{ Name = \"nginx\" }]")?;
+    config.caps.page_kb = 64;
+    config.caps.clk_tck = 250;
+    let mut info = ProcessWatchInfo::default();
+    info.parse_from_str(
+        "1000.00 3900.00\n--- 0\n100 20 100 (nginx) S 1 100 100 0 -1 4194560 100 0 0 0 5000 1000 0 0 20 0 1 0 1000 100000000 2560 0 0 0 0 0 0 0 0\n",
+        &config,
+    )?;
+    let nginx = &info.processes[0].instances[0];
+    assert_eq!(nginx.rss_kb, 2560 * 64);
+    // 启动于第 4 秒，累计 24 秒 CPU 时间
+    assert_eq!(nginx.uptime, 996.0);
+    assert!((nginx.cpu_percent - 24.0 / 996.0 * 100.0).abs() < 1e-9);
+    Ok(())
+}
+
+#[test]
+fn test_freebsd_monitors() -> anyhow::Result<()> {
+    let config = freebsd();
+    assert_eq!(
+        DiskInfo::default().exec_cmd(&config),
+        "df -kP -t notmpfs,devtmpfs,devfs,autofs; echo '---'; df -ki -t notmpfs,devtmpfs,devfs,autofs"
+    );
+
+    let mut mem = MemInfo::default();
+    mem.parse_from_str(
+        "hw.physmem: 8589934592
+hw.pagesize: 4096
+vm.stats.vm.v_free_count: 262144
+vm.stats.vm.v_inactive_count: 262144
+vm.stats.vm.v_cache_count: 0
+Device          1K-blocks     Used    Avail Capacity
+/dev/ada0p3       2097152   524288  1572864    25%
+",
+        &config,
+    )?;
+    assert_eq!(mem.mem_total_kb, 8 * 1024 * 1024);
+    assert_eq!(mem.used_percent(), 75.0);
+    assert_eq!(mem.swap_used_percent(), Some(25.0));
+
+    // kern.cp_time: user nice sys intr idle
+    let cpu = |clock: &str, cp_time: &str| -> anyhow::Result<CpuInfo> {
+        let mut info = CpuInfo::default();
+        info.parse_from_str(
+            &format!("{clock}\n{cp_time}\n---\n  PID %CPU %MEM COMMAND\n  7 1.0 0.1 sshd\n"),
+            &config,
+        )?;
+        Ok(info)
+    };
+    let first = cpu("1697000000", "100 0 100 0 800")?;
+    // 秒级时间戳可能相同，仍按计数器求差
+    let mut second = cpu("1697000000", "150 0 120 10 820")?;
+    second.apply_previous(&first);
+    assert_eq!(second.usage_percent, 80.0);
+    assert_eq!(second.top_processes[0].name, "sshd");
+
+    // FreeBSD 的 netstat 比 macOS 多一列 Idrop
+    let netstat = |clock: &str, rx: u64| -> anyhow::Result<NetInfo> {
+        let mut info = NetInfo::default();
+        info.parse_from_str(
+            &format!(
+                "{clock}
+Name    Mtu Network       Address              Ipkts Ierrs Idrop     Ibytes    Opkts Oerrs     Obytes  Coll
+em0    1500 <Link#1>      08:00:27:aa:bb:cc    12345     0     0 {rx}     2345     0     234567     0
+em0       - 10.0.2.0/24   10.0.2.15            12000     -     -    1200000     2300     -     230000     -
+"
+            ),
+            &config,
+        )?;
+        Ok(info)
+    };
+    let first = netstat("1697000000", 1_000_000)?;
+    assert_eq!(first.interfaces.len(), 1);
+    assert_eq!(first.interfaces[0].rx_rate, 0.0);
+    let mut second = netstat("1697000002", 1_204_800)?;
+    second.apply_previous(&first);
+    assert_eq!(second.interfaces[0].rx_rate, 102_400.0);
+    assert_eq!(second.interfaces[0].tx_rate, 0.0);
+    Ok(())
+}
+
+fn custom(def: &str) -> MonitorExecConfig {
+    let monitor: CustomMonitor = toml::from_str(def).unwrap();
+    monitor.validate().unwrap();
+    let mut config = MonitorExecConfig::default();
+    config.opts.custom.push(monitor);
+    config
+}
+
+#[test]
+fn test_custom_monitor_parsers() -> anyhow::Result<()> {
+    let config = custom(
+        r#"
+        name = "queue"
+        command = "cat /var/run/queue_depth"
+        parser = "Number"
+        unit = "jobs"
+        "#,
+    );
+    let mut info = CustomInfo::new("queue");
+    assert_eq!(info.exec_cmd(&config), "cat /var/run/queue_depth");
+    info.parse_from_str("42\n", &config)?;
+    assert_eq!(
+        info.common_display(),
+        "queue:\n  queue                    42 jobs\n"
+    );
+    assert!(info.parse_from_str("n/a\n", &config).is_err());
+
+    let config = custom(
+        r#"
+        name = "es"
+        command = "curl -s localhost:9200/_cluster/health"
+        parser = { JsonPath = "$.shards[0]" }
+        fields.active = { label = "active shards" }
+        "#,
+    );
+    let mut info = CustomInfo::new("es");
+    info.parse_from_str(
+        r#"{"status": "green", "shards": [{"active": 10, "ratio": 0.5, "node": null}]}"#,
+        &config,
+    )?;
+    let display = info.common_display();
+    assert!(
+        display.contains("active shards            10\n"),
+        "{display}"
+    );
+    assert!(
+        display.contains("ratio                    0.50\n"),
+        "{display}"
+    );
+    assert!(!display.contains("node"), "{display}");
+
+    let config = custom(
+        r#"
+        name = "pg"
+        command = "psql -c 'select 1'"
+        parser = { Regex = '(?P<conns>\d+) connections, (?P<state>\w+)' }
+        "#,
+    );
+    let mut info = CustomInfo::new("pg");
+    info.parse_from_str("status: 17 connections, healthy\n", &config)?;
+    let keys: Vec<&str> = info.metrics.iter().map(|m| m.key.as_str()).collect();
+    assert_eq!(keys, ["conns", "state"]);
+    assert!(info.parse_from_str("down\n", &config).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_load_monitor() -> anyhow::Result<()> {
+    let config = MonitorExecConfig::default();
+    let mut load = LoadInfo::default();
+    load.parse_from_str(
+        "8.00 4.00 2.00 5/600 4242\n86400.00 100.00\nabc\nbtime 1700000000\n8\n",
+        &config,
+    )?;
+    assert_eq!(load.per_core(), [1.0, 0.5, 0.25]);
+    assert_eq!(
+        (load.running, load.tasks, load.last_pid),
+        (Some(5), Some(600), Some(4242))
+    );
+    assert!(
+        load.common_display()
+            .contains("Uptime: 1d 0h 0m, booted 2023-11-14 22:13:20 UTC")
+    );
+
+    // 开机时长增加且 boot_id 不变：无事件
+    let mut later = load.clone();
+    later.uptime += 10.0;
+    assert!(later.alerts(&load).is_empty());
+    // 开机时长回退：重启
+    let mut rebooted = load.clone();
+    rebooted.uptime = 30.0;
+    assert_eq!(rebooted.alerts(&load), vec![Alert::Reboot { uptime: 30.0 }]);
+
+    let mut bsd = LoadInfo::default();
+    bsd.parse_from_str(
+        "{ 1.52 1.60 1.71 }\n{ sec = 1700000000, usec = 123456 } Tue Nov 14 22:13:20 2023\n4\n1700003600\n",
+        &freebsd(),
+    )?;
+    assert_eq!(bsd.load, [1.52, 1.60, 1.71]);
+    assert_eq!(bsd.uptime, 3600.0);
+    assert_eq!(bsd.running, None);
+    assert_eq!(bsd.boot_id, "");
+    Ok(())
+}
+
+#[test]
+fn test_bsd_boottime_drift_is_not_a_reboot() -> anyhow::Result<()> {
+    let sample = |boot: i64, now: i64, uuid: &str, config: &MonitorExecConfig| {
+        let mut load = LoadInfo::default();
+        load.parse_from_str(
+            &format!("{{ 0.10 0.20 0.30 }}\n{{ sec = {boot}, usec = 0 }} Tue Nov 14 22:13:20 2023\n4\n{now}\n{uuid}"),
+            config,
+        )
+        .map(|()| load)
+    };
+    let macos = MonitorExecConfig {
+        os: RemoteOs::MacOs,
+        ..Default::default()
+    };
+    const UUID: &str = "5B0C3F2E-8D4A-4C1B-9E2F-6A7D8C9B0A1F";
+
+    // 校时后 kern.boottime 后移 5 秒，开机时长随之回退 4 秒：不是重启
+    let mac_first = sample(1700000000, 1700003600, UUID, &macos)?;
+    let drifted = sample(1700000005, 1700003601, UUID, &macos)?;
+    assert_eq!(drifted.uptime, 3596.0);
+    assert!(drifted.alerts(&mac_first).is_empty());
+    let bsd_first = sample(1700000000, 1700003600, "", &freebsd())?;
+    let drifted = sample(1700000005, 1700003601, "", &freebsd())?;
+    assert!(drifted.alerts(&bsd_first).is_empty());
+
+    // macOS 的开机会话 UUID 变化即为重启，即使开机时长更长
+    let rebooted = sample(
+        1700000100,
+        1700010000,
+        "0F1E2D3C-4B5A-6978-8796-A5B4C3D2E1F0",
+        &macos,
+    )?;
+    assert_eq!(rebooted.alerts(&mac_first).len(), 1);
+    // FreeBSD 只能靠开机时长回退发现重启
+    let rebooted = sample(1700003540, 1700003660, "", &freebsd())?;
+    assert_eq!(
+        rebooted.alerts(&bsd_first),
+        vec![Alert::Reboot { uptime: 120.0 }]
+    );
+    Ok(())
+}
@@ -0,0 +1,734 @@
+use crate::agent::{Limiter, spawn_agent};
+use crate::config::{Config, ReconnectConfig, ServerConfig};
+use crate::model::{Alert, ErrorKind, MonitorEvent, MonitorKind};
+use crate::ssh::JumpPool;
+use crate::ui::tui::render;
+use crate::ui::{AppState, main_text, page_kinds};
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+
+const KINDS: [MonitorKind; 4] = [
+    MonitorKind::Mem,
+    MonitorKind::Cpu,
+    MonitorKind::Disk,
+    MonitorKind::Net,
+];
+
+fn fixture_dir(name: &str) -> String {
+    format!("{}/src/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn replay_server(name: &str, fixtures: &str, monitors: &str) -> ServerConfig {
+    let config: Config = toml::from_str(&format!(
+        r#"
+        [global]
+        [[servers]]
+        name = "{name}"
+        transport = "replay"
+        fixtures = "{}"
+        monitors = {monitors}
+        poll_interval_ms = 100
+        "#,
+        fixture_dir(fixtures)
+    ))
+    .unwrap();
+    config.validate().unwrap();
+    config.servers.into_iter().next().unwrap()
+}
+
+/// 启动 agent，收集 `count` 个事件后发出退出信号
+async fn collect_events(server: ServerConfig, count: usize) -> Vec<MonitorEvent> {
+    let (tx, mut rx) = mpsc::channel(100);
+    let (shutdown_tx, _) = broadcast::channel(1);
+    let reconnect = ReconnectConfig {
+        initial_ms: 10,
+        max_ms: 50,
+        multiplier: 2.0,
+        jitter: 0.0,
+    };
+    let handle = spawn_agent(
+        server,
+        tx,
+        shutdown_tx.subscribe(),
+        reconnect,
+        JumpPool::default(),
+        Limiter::new(4),
+        Duration::ZERO,
+    );
+    let mut events = Vec::new();
+    while events.len() < count {
+        let ev = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("agent produced no event in time")
+            .expect("agent channel closed");
+        events.push(ev);
+    }
+    shutdown_tx.send(()).unwrap();
+    handle.await.unwrap();
+    events
+}
+
+fn screen_text(terminal: &Terminal<TestBackend>) -> String {
+    let buffer = terminal.backend().buffer();
+    let mut text = String::new();
+    for y in 0..buffer.area.height {
+        for x in 0..buffer.area.width {
+            text.push_str(buffer[(x, y)].symbol());
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// 回放两轮全部监控项，返回写入样本后的状态
+async fn replay_all(name: &str, fixtures: &str) -> AppState {
+    let server = replay_server(name, fixtures, r#"["Mem", "Cpu", "Disk", "Net"]"#);
+    // 第二轮的 CPU 与网络数据由两次采样求差得到
+    let events = collect_events(server, 8).await;
+
+    let mut state = AppState::new(vec![name.to_string()], KINDS.to_vec(), Limiter::new(4));
+    for ev in events {
+        assert!(matches!(ev, MonitorEvent::Sample { .. }), "{ev:?}");
+        state.update_event(ev);
+    }
+    state
+}
+
+/// 回放 `count` 个事件，写入只多出 `kind` 一页的状态并切换到该页
+async fn page_state(
+    server: ServerConfig,
+    kind: MonitorKind,
+    count: usize,
+) -> (Vec<MonitorEvent>, AppState) {
+    let name = server.name.clone();
+    let events = collect_events(server, count).await;
+    let mut kinds = KINDS.to_vec();
+    kinds.push(kind);
+    let mut state = AppState::new(vec![name], kinds, Limiter::new(4));
+    for ev in events.iter().cloned() {
+        state.update_event(ev);
+    }
+    state.handle_key(crossterm::event::KeyCode::Char('5'));
+    (events, state)
+}
+
+/// 同 [`page_state`]，返回 `kind` 页的文本
+async fn page_text(
+    server: ServerConfig,
+    kind: MonitorKind,
+    count: usize,
+) -> (Vec<MonitorEvent>, String) {
+    let (events, state) = page_state(server, kind, count).await;
+    (events, main_text(&state))
+}
+
+/// 依次切换到 MEM/CPU/DISK/NET 页面并取出文本
+fn pages(state: &mut AppState) -> Vec<String> {
+    (0..KINDS.len())
+        .map(|idx| {
+            state.handle_key(crossterm::event::KeyCode::Char((b'1' + idx as u8) as char));
+            main_text(state)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_replay_agent_end_to_end() {
+    let mut state = replay_all("web-01", "linux").await;
+    let pages = pages(&mut state);
+    assert!(
+        pages[0].contains("[MEM] Total Memory: 5.87 GB"),
+        "{}",
+        pages[0]
+    );
+    // 两次 /proc/stat 之间总计 200 jiffies，其中空闲 120
+    assert!(pages[1].contains("CPU Usage: 40.00%"), "{}", pages[1]);
+    assert!(pages[1].contains("java"));
+    // 两个核各自的 jiffies 与整体相同，0.2s 内 ctxt 增加 800、intr 增加 400
+    assert!(pages[1].contains("Cores (2): [▃▃]"), "{}", pages[1]);
+    assert!(
+        pages[1].contains("Context switches: 4000/s, interrupts: 2000/s"),
+        "{}",
+        pages[1]
+    );
+    assert!(
+        pages[2].contains("( 95.0%)* inodes (  2.0%), mount: /data"),
+        "{}",
+        pages[2]
+    );
+    // 块只用了 60%，inode 却快耗尽
+    assert!(
+        pages[2].contains("( 60.0%) inodes ( 98.0%)*, mount: /\n"),
+        "{}",
+        pages[2]
+    );
+    assert!(
+        pages[2].contains("inodes (    -  ), mount: /boot/efi"),
+        "{}",
+        pages[2]
+    );
+    assert!(
+        pages[2].contains("Total Inodes: 19353600, Used: 6678528 (34.51%)"),
+        "{}",
+        pages[2]
+    );
+    assert!(
+        pages[3].contains("eth0       RX: 1000.0 KB/s | TX: 200.0 KB/s"),
+        "{}",
+        pages[3]
+    );
+
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    render(&mut terminal, &state);
+    let screen = screen_text(&terminal);
+    assert!(screen.contains("Stalking Monitor"));
+    assert!(screen.contains("eth0"));
+
+    state.handle_key(crossterm::event::KeyCode::Char('2'));
+    render(&mut terminal, &state);
+    assert!(screen_text(&terminal).contains("Per-core usage"));
+}
+
+#[tokio::test]
+async fn test_replay_macos_end_to_end() {
+    let mut state = replay_all("mac-01", "darwin").await;
+    let pages = pages(&mut state);
+    assert!(
+        pages[0].contains(
+            "Total Memory: 16.00 GB, Used: 10.00 GB (62.50 %), Available: 6.00 GB, Swap Used: 25.00 %"
+        ),
+        "{}",
+        pages[0]
+    );
+    // 第二轮读到上一轮在后台采样的结果
+    assert!(pages[1].contains("CPU Usage: 18.75%"), "{}", pages[1]);
+    assert!(pages[1].contains("Google Chrome"), "{}", pages[1]);
+    assert!(
+        pages[2].contains("( 90.0%) inodes ( 99.0%)*, mount: /Volumes/Backup Disk"),
+        "{}",
+        pages[2]
+    );
+    // netstat 两次采样相隔 2.5s
+    assert!(
+        pages[3].contains("en0        RX: 1024.0 KB/s | TX: 100.0 KB/s"),
+        "{}",
+        pages[3]
+    );
+    assert!(pages[3].contains("utun0"), "{}", pages[3]);
+}
+
+#[tokio::test]
+async fn test_replay_macos_skips_linux_only_monitors() {
+    let server = replay_server("mac-02", "darwin", r#"["Mem", "Pressure", "Sensors"]"#);
+    let events = collect_events(server, 3).await;
+    // 连接后只报告一次，之后每轮只执行 Mem
+    match &events[0] {
+        MonitorEvent::Error {
+            kind: Some(MonitorKind::Pressure),
+            category: ErrorKind::Monitor,
+            error,
+            ..
+        } => assert_eq!(error, "Pressure, Sensors not supported on MacOs, skipped"),
+        ev => panic!("{ev:?}"),
+    }
+    for ev in &events[1..] {
+        assert!(
+            matches!(
+                ev,
+                MonitorEvent::Sample {
+                    kind: MonitorKind::Mem,
+                    ..
+                }
+            ),
+            "{ev:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_replay_busybox_end_to_end() {
+    let mut state = replay_all("alpine-01", "busybox").await;
+    let pages = pages(&mut state);
+    assert!(pages[1].contains("CPU Usage: 40.00%"), "{}", pages[1]);
+    // 进程名中的空格与括号保留原样
+    assert!(
+        pages[1].contains("42         java                  50.0% CPU  50.0% MEM"),
+        "{}",
+        pages[1]
+    );
+    assert!(pages[1].contains("my app (v2)"), "{}", pages[1]);
+    // BusyBox df 不支持 -x，tmpfs 由 /proc/mounts 过滤
+    assert!(
+        pages[2].contains("( 95.0%) inodes (    -  ), mount: /etc/hosts"),
+        "{}",
+        pages[2]
+    );
+    assert!(!pages[2].contains("/dev/shm"), "{}", pages[2]);
+    assert!(pages[3].contains("eth0"), "{}", pages[3]);
+}
+
+#[tokio::test]
+async fn test_replay_scripted_failures() {
+    let server = replay_server("flaky", "flaky", r#"["Mem", "Disk"]"#);
+    let events = collect_events(server, 4).await;
+
+    let summary: Vec<(Option<MonitorKind>, Option<ErrorKind>)> = events
+        .iter()
+        .map(|ev| match ev {
+            MonitorEvent::Sample { kind, .. } => (Some(kind.clone()), None),
+            MonitorEvent::Error { kind, category, .. } => (kind.clone(), Some(*category)),
+            MonitorEvent::Alert { .. } => panic!("unexpected alert: {ev:?}"),
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            // connect.toml 第一次连接失败
+            (None, Some(ErrorKind::Connect)),
+            // 执行中连接被重置，整批失败，agent 丢弃连接并重连
+            (None, Some(ErrorKind::Monitor)),
+            (Some(MonitorKind::Mem), None),
+            // 非零退出码不触发重连
+            (Some(MonitorKind::Disk), Some(ErrorKind::Monitor)),
+        ]
+    );
+
+    let mut state = AppState::new(vec!["flaky".to_string()], KINDS.to_vec(), Limiter::new(4));
+    for ev in events {
+        state.update_event(ev);
+    }
+    let text = main_text(&state);
+    assert!(text.contains("Used: 0.48 GB (50.00 %)"), "{text}");
+    assert!(
+        text.contains("!! ERROR: command exited with non-zero status: 1"),
+        "{text}"
+    );
+}
+
+#[tokio::test]
+async fn test_replay_host_key_mismatch_backs_off() {
+    let server = replay_server("bastion", "hostkey", r#"["Mem"]"#);
+    let (tx, mut rx) = mpsc::channel(100);
+    let (shutdown_tx, _) = broadcast::channel(1);
+    let reconnect = ReconnectConfig {
+        initial_ms: 20,
+        max_ms: 80,
+        multiplier: 2.0,
+        jitter: 0.0,
+    };
+    let handle = spawn_agent(
+        server,
+        tx,
+        shutdown_tx.subscribe(),
+        reconnect,
+        JumpPool::default(),
+        Limiter::new(4),
+        Duration::ZERO,
+    );
+
+    let mut attempts = Vec::new();
+    for _ in 0..5 {
+        let ev = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("agent produced no event in time")
+            .expect("agent channel closed");
+        let MonitorEvent::Error {
+            kind: None,
+            category: ErrorKind::HostKeyMismatch,
+            error,
+            ..
+        } = ev
+        else {
+            panic!("expected a host key error, got {ev:?}");
+        };
+        assert!(error.contains("key for 10.0.0.5 changed"), "{error}");
+        attempts.push((std::time::Instant::now(), error));
+    }
+    shutdown_tx.send(()).unwrap();
+    handle.await.unwrap();
+
+    // 每次失败后按退避等待再重连，而不是立即重试
+    let delays: Vec<&str> = attempts
+        .iter()
+        .map(|(_, e)| e.rsplit("retry in ").next().unwrap())
+        .collect();
+    assert_eq!(delays, vec!["20ms", "40ms", "80ms", "80ms", "80ms"]);
+    for (pair, expected) in attempts.windows(2).zip([20, 40, 80, 80]) {
+        let gap = pair[1].0 - pair[0].0;
+        assert!(
+            gap >= Duration::from_millis(expected),
+            "reconnected after {gap:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_replay_custom_monitor() {
+    let mut config: Config = toml::from_str(&format!(
+        r#"
+        [global]
+        [[servers]]
+        name = "cache-01"
+        transport = "replay"
+        fixtures = "{}"
+        monitors = ["Mem", {{ Custom = "redis" }}]
+        poll_interval_ms = 100
+
+        [[custom_monitors]]
+        name = "redis"
+        label = "Redis"
+        command = "redis-cli info stats"
+        parser = "KeyValue"
+        fields.instantaneous_ops_per_sec = {{ label = "ops", unit = "/s" }}
+        "#,
+        fixture_dir("linux")
+    ))
+    .unwrap();
+    config.resolve_custom_monitors();
+    config.validate().unwrap();
+    let kinds = page_kinds(&config.servers);
+    assert_eq!(kinds[4], MonitorKind::Custom("redis".to_string()));
+
+    let server = config.servers.into_iter().next().unwrap();
+    let events = collect_events(server, 2).await;
+    let mut state = AppState::new(vec!["cache-01".to_string()], kinds, Limiter::new(4));
+    for ev in events {
+        assert!(matches!(ev, MonitorEvent::Sample { .. }), "{ev:?}");
+        state.update_event(ev);
+    }
+    state.handle_key(crossterm::event::KeyCode::Char('5'));
+    let text = main_text(&state);
+    assert!(text.contains("[redis] Redis:"), "{text}");
+    assert!(text.contains("ops                      1520 /s"), "{text}");
+    assert!(text.contains("role                     master"), "{text}");
+    // 超过 4 页时提示 TAB 翻页
+    assert!(crate::ui::pages_help(&state).contains("MEM/CPU/DISK/NET/redis"));
+}
+
+#[tokio::test]
+async fn test_replay_plugin_monitor() {
+    let mut config: Config = toml::from_str(&format!(
+        r#"
+        [global]
+        [[servers]]
+        name = "cache-02"
+        transport = "replay"
+        fixtures = "{}"
+        monitors = [{{ Plugin = "redis" }}]
+        poll_interval_ms = 100
+
+        [[plugins]]
+        path = "{}"
+        "#,
+        fixture_dir("linux"),
+        fixture_dir("plugins/redis.sh")
+    ))
+    .unwrap();
+    config.resolve_plugins().await.unwrap();
+    config.validate().unwrap();
+    let kinds = page_kinds(&config.servers);
+
+    let server = config.servers.into_iter().next().unwrap();
+    let events = collect_events(server, 1).await;
+    let mut state = AppState::new(vec!["cache-02".to_string()], kinds, Limiter::new(4));
+    for ev in events {
+        assert!(matches!(ev, MonitorEvent::Sample { .. }), "{ev:?}");
+        state.update_event(ev);
+    }
+    state.handle_key(crossterm::event::KeyCode::Tab);
+    state.handle_key(crossterm::event::KeyCode::BackTab);
+    state.handle_key(crossterm::event::KeyCode::BackTab);
+    let text = main_text(&state);
+    assert!(text.contains("[redis] Redis:"), "{text}");
+    assert!(text.contains("ops                      1520 /s"), "{text}");
+    assert!(text.contains("role                     master"), "{text}");
+}
+
+#[tokio::test]
+async fn test_replay_plugin_timeout() {
+    let mut config: Config = toml::from_str(&format!(
+        r#"
+        [global]
+        [[servers]]
+        name = "cache-03"
+        transport = "replay"
+        fixtures = "{}"
+        monitors = [{{ Plugin = "slow" }}]
+        poll_interval_ms = 100
+
+        [[plugins]]
+        path = "{}"
+        timeout_ms = 200
+        "#,
+        fixture_dir("linux"),
+        fixture_dir("plugins/slow.sh")
+    ))
+    .unwrap();
+    config.resolve_plugins().await.unwrap();
+    config.validate().unwrap();
+
+    let server = config.servers.into_iter().next().unwrap();
+    let events = collect_events(server, 1).await;
+    match &events[0] {
+        MonitorEvent::Error {
+            kind: Some(MonitorKind::Plugin(name)),
+            category: ErrorKind::Monitor,
+            error,
+            ..
+        } => {
+            assert_eq!(name, "slow");
+            assert!(error.contains("timed out after 200ms"), "{error}");
+        }
+        ev => panic!("{ev:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_replay_reboot_alert() {
+    let server = replay_server("web-01", "linux", r#"["Load"]"#);
+    let (events, text) = page_text(server, MonitorKind::Load, 3).await;
+    assert!(
+        matches!(
+            &events[2],
+            MonitorEvent::Alert {
+                kind: MonitorKind::Load,
+                alert: Alert::Reboot { .. },
+                ..
+            }
+        ),
+        "{events:?}"
+    );
+    assert!(
+        text.contains("Load Average: 0.40 0.20 0.10 (per core: 0.10 0.05 0.03, 4 cores)"),
+        "{text}"
+    );
+    assert!(
+        text.contains("Tasks: 1 running / 398 total, last PID 1234"),
+        "{text}"
+    );
+    assert!(
+        text.contains("[LOAD] REBOOT: host restarted, up 20m 0s"),
+        "{text}"
+    );
+}
+
+#[tokio::test]
+async fn test_replay_diskio() {
+    let server = replay_server("web-01", "linux", r#"["DiskIo"]"#);
+    let (_, text) = page_text(server, MonitorKind::DiskIo, 2).await;
+    assert!(text.contains("await  aqu-sz  %util"), "{text}");
+    assert!(
+        text.contains(
+            "nvme0n1         100.0    100.0     1024.0      512.0   3.00ms    1.50  90.0%"
+        ),
+        "{text}"
+    );
+    assert!(!text.contains("nvme0n1p1"), "{text}");
+    assert!(!text.contains("loop0"), "{text}");
+}
+
+#[tokio::test]
+async fn test_replay_pressure_oom_alert() {
+    let server = replay_server("web-01", "linux", r#"["Pressure"]"#);
+    let (events, text) = page_text(server, MonitorKind::Pressure, 3).await;
+    assert!(
+        matches!(
+            &events[2],
+            MonitorEvent::Alert {
+                kind: MonitorKind::Pressure,
+                alert: Alert::OomKill { kills: 2 },
+                ..
+            }
+        ),
+        "{events:?}"
+    );
+    assert!(
+        text.contains("memory    35.10  10.20   2.40 |  30.00   8.75   1.95"),
+        "{text}"
+    );
+    assert!(
+        text.contains("Swap in: 200.0 pages/s, out: 500.0 pages/s, major faults: 250.0/s"),
+        "{text}"
+    );
+    assert!(text.contains("OOM kills since boot: 3"), "{text}");
+    assert!(
+        text.contains("[PRESSURE] OOM: 2 process(es) killed by the OOM killer"),
+        "{text}"
+    );
+}
+
+#[tokio::test]
+async fn test_replay_process_watch() {
+    let server = replay_server(
+        "web-01",
+        "linux",
+        r#"["Process"]
+        monitor_opts = { process = { watch = [{ Name = "nginx" }, { Regex = "java.*kafka" }, { Pidfile = "/run/postgresql/15-main.pid" }] } }"#,
+    );
+    let (events, text) = page_text(server, MonitorKind::Process, 4).await;
+    let alerts: Vec<&Alert> = events
+        .iter()
+        .filter_map(|ev| match ev {
+            MonitorEvent::Alert { alert, .. } => Some(alert),
+            _ => None,
+        })
+        .collect();
+    // nginx 只是换了工作进程，主进程未变
+    assert_eq!(
+        alerts,
+        [
+            &Alert::ProcessRestarted {
+                process: "java.*kafka".to_string(),
+                old_pid: 200,
+                new_pid: 300,
+            },
+            &Alert::ProcessGone {
+                process: "/run/postgresql/15-main.pid".to_string(),
+                pid: 400,
+            },
+        ]
+    );
+    // 主进程 2s 内用了 200 jiffies，新工作进程启动 1s 用了 10 jiffies
+    assert!(
+        text.contains(
+            "nginx                    running     2  110.0%     30.0MB       2     45  16m 32s (PID 100)"
+        ),
+        "{text}"
+    );
+    assert!(
+        text.contains("java.*kafka              running     1  120.0%   1024.0MB      64      -"),
+        "{text}"
+    );
+    assert!(
+        text.contains("/run/postgresql/15-main.pid DOWN        0"),
+        "{text}"
+    );
+    assert!(
+        text.contains("[PROC] PROCESS RESTARTED: java.*kafka PID 200 -> 300"),
+        "{text}"
+    );
+}
+
+#[tokio::test]
+async fn test_replay_systemd_units() {
+    let server = replay_server(
+        "web-01",
+        "linux",
+        r#"["Systemd"]
+        monitor_opts = { systemd = { units = ["nginx", "backup.service"] } }"#,
+    );
+    let (_, state) = page_state(server, MonitorKind::Systemd, 2).await;
+    let text = main_text(&state);
+    assert!(
+        text.contains("Units: 1 active, 1 failed, 1 other"),
+        "{text}"
+    );
+    // 30ms CPU / 2s；状态变化时间比远端 date 早 4002s
+    assert!(
+        text.contains("nginx.service                    active     running           0     50.0MB    1.5%  1h 6m"),
+        "{text}"
+    );
+    assert!(
+        text.contains(
+            "backup.service                   inactive   dead              0          -       -  -"
+        ),
+        "{text}"
+    );
+    assert!(
+        text.contains("worker.service                   failed     failed            5          -       -  0m 12s"),
+        "{text}"
+    );
+
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    render(&mut terminal, &state);
+    let buffer = terminal.backend().buffer();
+    let screen = screen_text(&terminal);
+    assert!(
+        screen.contains("Units: 1 active, 1 failed, 1 other"),
+        "{screen}"
+    );
+    // 文本中只有摘要，每个 unit 只在表格中出现一次
+    for unit in ["nginx.service", "backup.service", "worker.service"] {
+        assert_eq!(screen.matches(unit).count(), 1, "{screen}");
+    }
+    // 表格中每行的颜色随状态变化
+    let color_of = |unit: &str| {
+        let (y, line) = screen
+            .lines()
+            .enumerate()
+            .find(|(_, line)| line.contains(&format!("│{unit}")))
+            .unwrap_or_else(|| panic!("{unit} not in table:\n{screen}"));
+        let x = line.chars().position(|c| c == '│').unwrap() + 1;
+        buffer[(x as u16, y as u16)].fg
+    };
+    assert_eq!(color_of("nginx.service"), ratatui::style::Color::Green);
+    assert_eq!(color_of("worker.service"), ratatui::style::Color::Red);
+    assert_eq!(color_of("backup.service"), ratatui::style::Color::Yellow);
+
+    // 终端太矮时表格被截断，摘要与帮助行仍然可见
+    let mut terminal = Terminal::new(TestBackend::new(100, 10)).unwrap();
+    render(&mut terminal, &state);
+    let screen = screen_text(&terminal);
+    assert!(
+        screen.contains("Units: 1 active, 1 failed, 1 other"),
+        "{screen}"
+    );
+    assert!(screen.contains("[Q] QUIT"), "{screen}");
+}
+
+#[tokio::test]
+async fn test_replay_sensors() {
+    let server = replay_server("db-01", "linux", r#"["Sensors"]"#);
+    let (_, text) = page_text(server, MonitorKind::Sensors, 1).await;
+    assert!(text.contains("Temperatures:"), "{text}");
+    assert!(
+        text.contains("nvme           Composite          41.9°C  (high 81.8°C, crit 84.8°C)"),
+        "{text}"
+    );
+    assert!(text.contains("Fans:"), "{text}");
+}
+
+#[tokio::test]
+async fn test_replay_sockets_listen_alerts() {
+    let server = replay_server("web-01", "linux", r#"["Sockets"]"#);
+    let (events, text) = page_text(server, MonitorKind::Sockets, 4).await;
+    let alerts: Vec<String> = events
+        .iter()
+        .filter_map(|ev| match ev {
+            MonitorEvent::Alert {
+                kind: MonitorKind::Sockets,
+                alert,
+                ..
+            } => Some(alert.to_string()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        alerts,
+        vec![
+            "PORT OPENED: tcp *:8080 (java, PID 4321)",
+            "PORT CLOSED: tcp 127.0.0.1:6379 (redis-server, PID 950)",
+        ]
+    );
+    assert!(
+        text.contains("TCP: 26 established, 128 time-wait, 3 close-wait, 4 other"),
+        "{text}"
+    );
+    // 2s 内发送 2000 段、重传 40 段
+    assert!(
+        text.contains(
+            "Retransmits: 20.0 segs/s (2.00%), listen overflows: 3.0/s, UDP errors: 0.0/s"
+        ),
+        "{text}"
+    );
+    // 临时端口上的 UDP 客户端套接字不算监听
+    assert!(text.contains("Listening (5):"), "{text}");
+    assert!(!text.contains(":52210"), "{text}");
+    assert!(
+        text.contains("  udp             127.0.0.53%lo:53    systemd-resolve (611)"),
+        "{text}"
+    );
+    assert!(text.contains(">> "), "{text}");
+}
@@ -0,0 +1,145 @@
+use crate::config::{AuthKind, Config, HostKeyCheck};
+use crate::ssh::{HostKeyError, HostKeyVerifier};
+use russh::keys::PublicKey;
+
+const KEY_A: &str =
+    "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIMiSVXiE13JF0TacgN6juU/p16g/Y0UU/0kz7BTtzPJN";
+const KEY_A_FP: &str = "SHA256:ZZLKzUVvWni562Sqr3g2VWK8o6LzU05YZochCJYNvLI";
+const KEY_B: &str =
+    "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIC7agcuc1uMWyq3zLL+uYRXv1WUnX0jyIXgFYOnWY0kP";
+
+fn key(s: &str) -> PublicKey {
+    PublicKey::from_openssh(s).unwrap()
+}
+
+fn verify(check: HostKeyCheck, k: &str) -> Result<(), HostKeyError> {
+    HostKeyVerifier::new("10.0.0.1", 22, check).verify(&key(k))
+}
+
+#[test]
+fn test_fingerprint_pin() {
+    assert!(verify(HostKeyCheck::Fingerprint(KEY_A_FP.into()), KEY_A).is_ok());
+    // 允许省略 SHA256: 前缀
+    let bare = KEY_A_FP.trim_start_matches("SHA256:").to_string();
+    assert!(verify(HostKeyCheck::Fingerprint(bare), KEY_A).is_ok());
+    let err = verify(HostKeyCheck::Fingerprint(KEY_A_FP.into()), KEY_B).unwrap_err();
+    assert!(err.reason.contains("fingerprint mismatch"));
+    assert!(err.mismatch);
+}
+
+#[test]
+fn test_known_hosts_file() {
+    let path = std::env::temp_dir().join(format!("stalking_known_hosts_{}", std::process::id()));
+    std::fs::write(&path, format!("10.0.0.1 {KEY_A}\n")).unwrap();
+
+    assert!(verify(HostKeyCheck::KnownHostsFile(path.clone()), KEY_A).is_ok());
+    let changed = verify(HostKeyCheck::KnownHostsFile(path.clone()), KEY_B).unwrap_err();
+    assert!(changed.reason.contains("line 1"));
+    assert!(changed.mismatch);
+    let unknown = HostKeyVerifier::new("10.0.0.2", 22, HostKeyCheck::KnownHostsFile(path.clone()))
+        .verify(&key(KEY_A))
+        .unwrap_err();
+    assert!(unknown.reason.contains("not found"));
+    assert!(!unknown.mismatch);
+
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn test_insecure_accepts_any_key() {
+    assert!(verify(HostKeyCheck::Insecure, KEY_B).is_ok());
+}
+
+#[test]
+fn test_host_key_check_config() {
+    let config: Config = toml::from_str(
+        r#"
+        [global]
+        [[servers]]
+        name = "a"
+        host = "10.0.0.1"
+        user = "root"
+        monitors = ["Mem"]
+
+        [[servers]]
+        name = "b"
+        host = "10.0.0.2"
+        user = "root"
+        monitors = ["Mem"]
+        host_key_check = { Fingerprint = "SHA256:abc" }
+
+        [[servers]]
+        name = "c"
+        host = "10.0.0.3"
+        user = "root"
+        monitors = ["Mem"]
+        host_key_check = "Insecure"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.servers[0].host_key_check, HostKeyCheck::KnownHosts);
+    assert_eq!(
+        config.servers[1].host_key_check,
+        HostKeyCheck::Fingerprint("SHA256:abc".into())
+    );
+    assert_eq!(config.servers[2].host_key_check, HostKeyCheck::Insecure);
+    assert!(config.validate().is_ok());
+    assert!(
+        HostKeyCheck::Fingerprint("MD5:aa:bb".into())
+            .validate()
+            .is_err()
+    );
+}
+
+#[test]
+fn test_auth_kind_selection() {
+    let config: Config = toml::from_str(
+        r#"
+        [global]
+        [[servers]]
+        name = "key"
+        host = "10.0.0.1"
+        user = "root"
+        privkey_path = "/root/.ssh/id_ed25519"
+        password = "unused"
+        monitors = ["Mem"]
+
+        [[servers]]
+        name = "pswd"
+        host = "10.0.0.2"
+        user = "root"
+        password = "secret"
+        monitors = ["Mem"]
+
+        [[servers]]
+        name = "implicit-agent"
+        host = "10.0.0.3"
+        user = "root"
+        monitors = ["Mem"]
+
+        [[servers]]
+        name = "explicit-agent"
+        host = "10.0.0.4"
+        user = "root"
+        privkey_path = "/root/.ssh/id_ed25519"
+        auth = "agent"
+        monitors = ["Mem"]
+        "#,
+    )
+    .unwrap();
+    let kinds: Vec<AuthKind> = config.servers.iter().map(|s| s.auth_kind()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            AuthKind::Key,
+            AuthKind::Password,
+            AuthKind::Agent,
+            AuthKind::Agent
+        ]
+    );
+    assert!(config.validate().is_ok());
+
+    let mut server = config.servers[2].clone();
+    server.auth = Some(AuthKind::Key);
+    assert!(server.validate().is_err());
+}
@@ -0,0 +1,160 @@
+use crate::config::Config;
+use crate::ssh_config::{SshConfig, wildcard_match};
+use std::path::PathBuf;
+
+const SSH_CONFIG: &str = r#"
+# 全局默认
+User fallback
+
+Host web-01 web-02
+    HostName 10.0.1.%h
+    Port 2200
+    IdentityFile /keys/web
+
+Host *.prod !db.prod
+    User deploy
+    ProxyJump bastion
+
+Host bastion
+    HostName 203.0.113.9
+    User jumper
+    IdentityFile /keys/bastion
+
+Host web-*
+    Port 9999
+    User ignored
+
+Host=db.prod
+    HostName "10.9.9.9"
+"#;
+
+#[test]
+fn test_wildcard_match() {
+    assert!(wildcard_match("*", "anything"));
+    assert!(wildcard_match("web-??", "WEB-01"));
+    assert!(wildcard_match("*.prod", "api.prod"));
+    assert!(!wildcard_match("*.prod", "api.staging"));
+    assert!(wildcard_match("a*b*c", "aXXbYYc"));
+    assert!(!wildcard_match("a*b*c", "aXXbYY"));
+}
+
+#[test]
+fn test_first_match_wins() {
+    let ssh = SshConfig::parse(SSH_CONFIG).unwrap();
+
+    let web = ssh.resolve("web-01");
+    assert_eq!(web.host_name.as_deref(), Some("10.0.1.web-01"));
+    // 第一个匹配块给出的 Port 生效，后面的 web-* 被忽略
+    assert_eq!(web.port, Some(2200));
+    // 全局 User 出现在所有 Host 块之前，优先于后面的 User
+    assert_eq!(web.user.as_deref(), Some("fallback"));
+    assert_eq!(web.identity_file, Some(PathBuf::from("/keys/web")));
+
+    let api = ssh.resolve("api.prod");
+    assert_eq!(api.proxy_jump.as_deref(), Some("bastion"));
+    assert_eq!(api.host_name, None);
+
+    // 取反模式排除 db.prod
+    let db = ssh.resolve("db.prod");
+    assert_eq!(db.proxy_jump, None);
+    assert_eq!(db.host_name.as_deref(), Some("10.9.9.9"));
+}
+
+#[tokio::test]
+async fn test_load_config_with_ssh_alias() {
+    let dir = std::env::temp_dir().join(format!("stalking_ssh_config_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let ssh_path = dir.join("ssh_config");
+    std::fs::write(
+        &ssh_path,
+        "Host app\n  HostName 10.2.0.1\n  User app\n  Port 2222\n  IdentityFile /keys/app\n  ProxyJump ops@gate:2022\n\
+         Host gate\n  HostName 198.51.100.7\n  IdentityFile /keys/gate\n",
+    )
+    .unwrap();
+    let config_path = dir.join("config.toml");
+    std::fs::write(
+        &config_path,
+        format!(
+            r#"
+            [global]
+            ssh_config = "{}"
+
+            [[servers]]
+            name = "app"
+            ssh_alias = "app"
+            monitors = ["Mem"]
+
+            [[servers]]
+            name = "app-explicit"
+            ssh_alias = "app"
+            user = "root"
+            port = 22
+            monitors = ["Mem"]
+            "#,
+            ssh_path.display()
+        ),
+    )
+    .unwrap();
+
+    let config = Config::load_config(&config_path).await.unwrap();
+    let app = &config.servers[0];
+    assert_eq!(app.host, "10.2.0.1");
+    assert_eq!(app.user, "app");
+    assert_eq!(app.port, 2222);
+    assert_eq!(app.privkey_path, Some(PathBuf::from("/keys/app")));
+    assert_eq!(app.jump_chain.len(), 1);
+    assert_eq!(app.jump_chain[0].label(), "ops@198.51.100.7:2022");
+    assert_eq!(
+        app.jump_chain[0].privkey_path,
+        Some(PathBuf::from("/keys/gate"))
+    );
+
+    // TOML 中显式填写的值优先
+    let explicit = &config.servers[1];
+    assert_eq!(explicit.user, "root");
+    assert_eq!(explicit.port, 22);
+    assert_eq!(explicit.host, "10.2.0.1");
+
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[tokio::test]
+async fn test_ssh_alias_with_ipv6_proxy_jump() {
+    let dir = std::env::temp_dir().join(format!("stalking_ssh_config_v6_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let ssh_path = dir.join("ssh_config");
+    // 带端口的 IPv6 跳板写在方括号中，不带端口时整体是主机
+    std::fs::write(
+        &ssh_path,
+        "Host app6\n  HostName 2001:db8::10\n  ProxyJump ops@[2001:db8::1]:2222,root@2001:db8::2\n",
+    )
+    .unwrap();
+    let config_path = dir.join("config.toml");
+    std::fs::write(
+        &config_path,
+        format!(
+            r#"
+            [global]
+            ssh_config = "{}"
+
+            [[servers]]
+            name = "app6"
+            ssh_alias = "app6"
+            user = "app"
+            monitors = ["Mem"]
+            "#,
+            ssh_path.display()
+        ),
+    )
+    .unwrap();
+
+    let config = Config::load_config(&config_path).await.unwrap();
+    let labels: Vec<String> = config.servers[0]
+        .jump_chain
+        .iter()
+        .map(|hop| hop.label())
+        .collect();
+    assert_eq!(labels, ["ops@2001:db8::1:2222", "root@2001:db8::2:22"]);
+
+    std::fs::remove_dir_all(dir).ok();
+}
@@ -0,0 +1,114 @@
+use crate::model::{MonitorKind, MonitorPayload};
+use crate::monitor::{MonitorExecConfig, Monitorable};
+use crate::transport::{BatchScript, CommandOutput, LocalTransport, ReplayTransport, Transport};
+
+#[tokio::test]
+async fn test_local_execute() -> anyhow::Result<()> {
+    let local = LocalTransport;
+    let out = local.execute("echo hello; echo oops >&2").await?;
+    assert_eq!(out.exit_status, 0);
+    assert_eq!(out.stdout.trim(), "hello");
+    assert_eq!(out.stderr.trim(), "oops");
+
+    let out = local.execute("exit 3").await?;
+    assert_eq!(out.exit_status, 3);
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_local_exec_monitor() -> anyhow::Result<()> {
+    let transport: Box<dyn Transport> = Box::new(LocalTransport);
+    let payload = transport
+        .exec_all(
+            vec![MonitorPayload::Mem(Default::default())],
+            &MonitorExecConfig::default(),
+        )
+        .await?
+        .remove(0)?;
+    assert!(matches!(&payload, MonitorPayload::Mem(info) if info.mem_total_kb > 0));
+    assert!(payload.common_display().starts_with("Total Memory"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_local_execute_batch() -> anyhow::Result<()> {
+    let local = LocalTransport;
+    let outputs = local
+        .execute_batch(&[
+            "echo a; echo e >&2",
+            "printf 'no newline'",
+            "exit 3",
+            "echo after",
+        ])
+        .await?;
+    let outputs: Vec<CommandOutput> = outputs.into_iter().collect::<anyhow::Result<_>>()?;
+    assert_eq!(outputs[0].stdout, "a\n");
+    assert_eq!(outputs[0].stderr, "e\n");
+    assert_eq!(outputs[1].stdout, "no newline");
+    assert_eq!(outputs[2].exit_status, 3);
+    // 子 shell 中的 exit 不会中断后续命令
+    assert_eq!(outputs[3].stdout, "after\n");
+    assert_eq!(outputs[3].exit_status, 0);
+    Ok(())
+}
+
+#[test]
+fn test_batch_split_truncated_output() {
+    let batch = BatchScript::new(&["echo one", "echo two"]);
+    let full = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(&batch.script)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&full.stdout).into_owned();
+    // 模拟脚本在第二段中途被中断
+    let cut = stdout.rfind("two").unwrap();
+    let outputs = batch.split(&CommandOutput {
+        stdout: stdout[..cut].to_string(),
+        stderr: String::new(),
+        exit_status: 0,
+    });
+    assert_eq!(outputs[0].as_ref().unwrap().stdout, "one\n");
+    assert!(outputs[1].is_err());
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_local_exec_all_monitors() -> anyhow::Result<()> {
+    let transport: Box<dyn Transport> = Box::new(LocalTransport);
+    let kinds = [MonitorKind::Mem, MonitorKind::Cpu, MonitorKind::Net];
+    let results = transport
+        .exec_all(
+            kinds.iter().map(MonitorPayload::from).collect(),
+            &MonitorExecConfig::default(),
+        )
+        .await?;
+    assert_eq!(results.len(), 3);
+    assert!(matches!(&results[0], Ok(MonitorPayload::Mem(info)) if info.mem_total_kb > 0));
+    assert!(matches!(&results[1], Ok(MonitorPayload::Cpu(_))));
+    assert!(matches!(&results[2], Ok(MonitorPayload::Net(_))));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_replay_cursors_belong_to_instance() -> anyhow::Result<()> {
+    let dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/tests/fixtures/flaky");
+    let first = ReplayTransport::new(&dir)?;
+    let second = ReplayTransport::new(&dir)?;
+
+    // 两个实例各自从 connect.toml 的第一条响应开始
+    assert!(first.connect().await.is_err());
+    assert!(second.connect().await.is_err());
+    let conn = first.connect().await?;
+    assert!(conn.execute("cat /proc/meminfo").await.is_err());
+
+    // 重连后的连接与之前共享计数，脚本继续向后推进
+    let reconnected = first.connect().await?;
+    let out = reconnected.execute("cat /proc/meminfo").await?;
+    assert!(out.stdout.contains("MemTotal"), "{}", out.stdout);
+
+    let other = second.connect().await?;
+    assert!(other.execute("cat /proc/meminfo").await.is_err());
+    Ok(())
+}
@@ -0,0 +1,75 @@
+//! 把多条命令拼成一个脚本一次执行，再按分隔标记拆回各自的输出。
+//!
+//! 每段输出前后都在 stdout 与 stderr 上写入带随机 nonce 的标记，
+//! 结束标记同时携带该段的退出码：
+//!
+//! ```text
+//! @@stalking-<nonce>:B:<idx>
+//! ...命令输出...
+//! @@stalking-<nonce>:E:<idx>:<exit>
+//! ```
+//!
+//! 命令在子 shell 中执行，单条命令 `exit` 或失败不会影响后续各段。
+
+use crate::transport::CommandOutput;
+use anyhow::Result;
+use rand::Rng;
+
+/// 一次批量执行的脚本与用于拆分输出的标记前缀
+pub struct BatchScript {
+    tag: String,
+    pub script: String,
+    count: usize,
+}
+
+impl BatchScript {
+    pub fn new(commands: &[&str]) -> Self {
+        let tag = format!("@@stalking-{:016x}", rand::rng().random::<u64>());
+        let mut script = String::new();
+        for (idx, command) in commands.iter().enumerate() {
+            // 前导换行保证标记独占一行，即使前面的输出没有以换行结尾
+            script.push_str(&format!(
+                "printf '\\n%s\\n' '{tag}:B:{idx}'; printf '\\n%s\\n' '{tag}:B:{idx}' >&2\n"
+            ));
+            script.push_str(&format!("(\n{command}\n)\n"));
+            script.push_str(&format!(
+                "__rc=$?; printf '\\n%s:%s\\n' '{tag}:E:{idx}' \"$__rc\"; printf '\\n%s:%s\\n' '{tag}:E:{idx}' \"$__rc\" >&2\n"
+            ));
+        }
+        Self {
+            tag,
+            script,
+            count: commands.len(),
+        }
+    }
+
+    /// 把整个脚本的输出拆成每条命令各自的结果，缺失的段返回错误
+    pub fn split(&self, output: &CommandOutput) -> Vec<Result<CommandOutput>> {
+        (0..self.count)
+            .map(|idx| {
+                let (stdout, exit_status) = self.section(&output.stdout, idx).ok_or_else(|| {
+                    anyhow::anyhow!("batch output is missing section {idx} (script aborted?)")
+                })?;
+                let stderr = self
+                    .section(&output.stderr, idx)
+                    .map(|(s, _)| s)
+                    .unwrap_or_default();
+                Ok(CommandOutput {
+                    stdout,
+                    stderr,
+                    exit_status,
+                })
+            })
+            .collect()
+    }
+
+    fn section(&self, stream: &str, idx: usize) -> Option<(String, u32)> {
+        let begin = format!("{}:B:{idx}\n", self.tag);
+        let end = format!("\n{}:E:{idx}:", self.tag);
+        let start = stream.find(&begin)? + begin.len();
+        let len = stream[start..].find(&end)?;
+        let rest = &stream[start + len + end.len()..];
+        let code = rest.lines().next()?.trim().parse().ok()?;
+        Some((stream[start..start + len].to_string(), code))
+    }
+}
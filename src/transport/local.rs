@@ -0,0 +1,29 @@
+use crate::transport::{CommandOutput, Transport};
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::process::Command;
+
+/// 在运行 stalking 的本机上通过 `sh -c` 执行命令，无需 SSH
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LocalTransport;
+
+#[async_trait]
+impl Transport for LocalTransport {
+    async fn execute(&self, command: &str) -> Result<CommandOutput> {
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .kill_on_drop(true)
+            .output()
+            .await?;
+        let exit_status = output
+            .status
+            .code()
+            .ok_or_else(|| anyhow::anyhow!("command terminated by signal"))?;
+        Ok(CommandOutput {
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            exit_status: exit_status as u32,
+        })
+    }
+}
@@ -0,0 +1,137 @@
+//! 监控命令的执行后端。
+//!
+//! agent 只依赖 [`Transport`]：SSH 连接远程主机，`local` 直接在本机执行，
+//! `replay` 从夹具目录回放输出。
+
+mod batch;
+pub use batch::BatchScript;
+pub mod local;
+pub use local::LocalTransport;
+pub mod replay;
+pub use replay::ReplayTransport;
+
+use crate::Monitorable;
+use crate::config::{ServerConfig, TransportKind};
+use crate::monitor::{self, MonitorExecConfig, RemoteOs, ToolCaps};
+use crate::ssh::{JumpPool, SSHClient};
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// 命令的执行结果
+#[derive(Debug, Clone)]
+pub struct CommandOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_status: u32,
+}
+
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// 执行一条 shell 命令，收集 stdout/stderr 与退出码
+    async fn execute(&self, command: &str) -> Result<CommandOutput>;
+
+    /// 一次执行多条命令，外层错误表示整批失败（通常是连接断开）。
+    ///
+    /// 默认把命令拼成单个脚本执行，每轮采样只需一次往返。
+    async fn execute_batch(&self, commands: &[&str]) -> Result<Vec<Result<CommandOutput>>> {
+        let batch = BatchScript::new(commands);
+        let output = self.execute(&batch.script).await?;
+        Ok(batch.split(&output))
+    }
+
+    /// 底层连接是否已经失效，失效后 agent 会重新建立
+    fn is_closed(&self) -> bool {
+        false
+    }
+
+    async fn disconnect(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl dyn Transport + '_ {
+    /// 识别远端系统与可用工具，见 [`monitor::PROBE_CMD`]
+    pub async fn probe(&self) -> Result<(RemoteOs, ToolCaps)> {
+        let output = self.execute(monitor::PROBE_CMD).await?;
+        if output.exit_status != 0 {
+            anyhow::bail!("probe exited with non-zero status: {}", output.exit_status);
+        }
+        monitor::parse_probe(&output.stdout)
+    }
+
+    /// 在一次批量执行中采集所有监控项，按输入顺序返回各自的结果
+    pub async fn exec_all<T: Monitorable>(
+        &self,
+        monitors: Vec<T>,
+        config: &MonitorExecConfig,
+    ) -> Result<Vec<Result<T>>> {
+        let commands: Vec<String> = monitors.iter().map(|m| m.exec_cmd(config)).collect();
+        let commands: Vec<&str> = commands.iter().map(String::as_str).collect();
+        let outputs = self.execute_batch(&commands).await?;
+        Ok(monitors
+            .into_iter()
+            .zip(outputs)
+            .map(|(monitor, output)| parse_output(monitor, output?, config))
+            .collect())
+    }
+}
+
+fn parse_output<T: Monitorable>(
+    mut monitor: T,
+    result: CommandOutput,
+    config: &MonitorExecConfig,
+) -> Result<T> {
+    match result.exit_status {
+        0 => {
+            monitor.parse_from_str(&result.stdout, config)?;
+            Ok(monitor)
+        }
+        code => Err(anyhow::anyhow!(
+            "command exited with non-zero status: {}",
+            code
+        )),
+    }
+}
+
+/// 按服务器配置建立执行后端，由 agent 持有并在每次重连时复用
+pub struct Connector {
+    pool: JumpPool,
+    /// 首次连接时加载的回放夹具；重连沿用同一份调用计数，脚本继续向后推进
+    replay: Option<ReplayTransport>,
+}
+
+impl Connector {
+    pub fn new(pool: JumpPool) -> Self {
+        Self { pool, replay: None }
+    }
+
+    pub async fn connect(&mut self, server: &ServerConfig) -> Result<Box<dyn Transport>> {
+        match server.transport {
+            TransportKind::Ssh => Ok(Box::new(
+                SSHClient::connect_from_config(server, &self.pool).await?,
+            )),
+            TransportKind::Local => Ok(Box::new(LocalTransport)),
+            TransportKind::Replay => {
+                let replay = match &self.replay {
+                    Some(replay) => replay,
+                    None => {
+                        let dir = server.fixtures.as_ref().ok_or_else(|| {
+                            anyhow::anyhow!(
+                                "server {} uses replay transport without fixtures",
+                                server.name
+                            )
+                        })?;
+                        self.replay.insert(ReplayTransport::new(dir)?)
+                    }
+                };
+                Ok(Box::new(replay.connect().await?))
+            }
+        }
+    }
+}
+
+/// 判断错误是否来自传输层（而不是命令退出码或解析失败），
+/// 这类错误意味着需要重新建立连接。
+pub fn is_transport_error(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<russh::Error>().is_some() || err.downcast_ref::<std::io::Error>().is_some()
+}
@@ -0,0 +1,193 @@
+//! 基于夹具目录回放命令输出的后端，用于无网络的端到端测试与演示。
+//!
+//! 目录中每个 `*.toml` 描述一条命令，按文件名顺序匹配：
+//!
+//! ```toml
+//! contains = "/proc/meminfo"     # 或 command = "完整命令"，精确匹配
+//!
+//! [[responses]]                  # 依次使用，用完后重复最后一条
+//! stdout_file = "meminfo.txt"    # 相对于夹具目录；也可用 stdout = "..."
+//!
+//! [[responses]]
+//! error = "connection reset"     # 模拟传输层错误
+//!
+//! [[responses]]
+//! delay_ms = 500                 # 模拟慢命令
+//! exit_status = 1
+//! ```
+//!
+//! 名为 `connect.toml` 的文件不匹配命令，而是在每次建立连接时消费一条响应，
+//! 可用 `error` 模拟连接失败，或用 `host_key = "原因"` 模拟主机密钥与记录不符。
+//!
+//! 调用计数属于 [`ReplayTransport::new`] 加载出的实例，同一目录的多个实例互不影响；
+//! agent 在重连时复用同一实例（见 [`Connector`](super::Connector)），脚本继续向后推进。
+
+use crate::ssh::HostKeyError;
+use crate::transport::{CommandOutput, Transport, is_transport_error};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+#[derive(Debug, Deserialize)]
+struct Fixture {
+    command: Option<String>,
+    contains: Option<String>,
+    #[serde(default)]
+    responses: Vec<Response>,
+    #[serde(skip)]
+    calls: AtomicUsize,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+struct Response {
+    #[serde(default)]
+    stdout: String,
+    stdout_file: Option<PathBuf>,
+    #[serde(default)]
+    stderr: String,
+    #[serde(default)]
+    exit_status: u32,
+    #[serde(default)]
+    delay_ms: u64,
+    error: Option<String>,
+    host_key: Option<String>,
+}
+
+impl Fixture {
+    fn matches(&self, command: &str) -> bool {
+        match (&self.command, &self.contains) {
+            (Some(exact), _) => exact == command,
+            (None, Some(part)) => command.contains(part.as_str()),
+            (None, None) => false,
+        }
+    }
+
+    /// 取下一条脚本响应，用完后重复最后一条
+    fn next(&self) -> Response {
+        let idx = self.calls.fetch_add(1, Ordering::Relaxed);
+        self.responses
+            .get(idx.min(self.responses.len().saturating_sub(1)))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Default)]
+struct FixtureSet {
+    connect: Option<Fixture>,
+    commands: Vec<Fixture>,
+}
+
+impl FixtureSet {
+    fn load(dir: &Path) -> Result<Self> {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+            .with_context(|| format!("failed to read fixture dir {}", dir.display()))?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+
+        let mut set = FixtureSet::default();
+        for path in paths {
+            let content = std::fs::read_to_string(&path)?;
+            let mut fixture: Fixture = toml::from_str(&content)
+                .with_context(|| format!("invalid fixture {}", path.display()))?;
+            for response in &mut fixture.responses {
+                if let Some(file) = response.stdout_file.take() {
+                    response.stdout = std::fs::read_to_string(dir.join(&file))
+                        .with_context(|| format!("failed to read {}", file.display()))?;
+                }
+            }
+            if path.file_stem().is_some_and(|s| s == "connect") {
+                set.connect = Some(fixture);
+            } else if fixture.command.is_none() && fixture.contains.is_none() {
+                anyhow::bail!(
+                    "fixture {} has neither command nor contains",
+                    path.display()
+                );
+            } else {
+                set.commands.push(fixture);
+            }
+        }
+        Ok(set)
+    }
+}
+
+/// 模拟一次传输层失败，agent 会把它当作断线处理
+fn transport_error(msg: &str) -> anyhow::Error {
+    std::io::Error::new(std::io::ErrorKind::ConnectionReset, msg.to_string()).into()
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplayTransport {
+    dir: PathBuf,
+    fixtures: Arc<FixtureSet>,
+}
+
+impl ReplayTransport {
+    /// 加载夹具目录，所有脚本从第一条响应开始
+    pub fn new(dir: &Path) -> Result<Self> {
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            fixtures: Arc::new(FixtureSet::load(dir)?),
+        })
+    }
+
+    /// 执行 `connect.toml` 中脚本化的连接结果；成功时返回共享调用计数的连接
+    pub async fn connect(&self) -> Result<Self> {
+        if let Some(connect) = &self.fixtures.connect {
+            let response = connect.next();
+            tokio::time::sleep(Duration::from_millis(response.delay_ms)).await;
+            if let Some(err) = response.error {
+                return Err(transport_error(&err));
+            }
+            if let Some(reason) = response.host_key {
+                return Err(HostKeyError {
+                    host: self.dir.display().to_string(),
+                    reason,
+                    mismatch: true,
+                }
+                .into());
+            }
+        }
+        Ok(self.clone())
+    }
+}
+
+#[async_trait]
+impl Transport for ReplayTransport {
+    async fn execute(&self, command: &str) -> Result<CommandOutput> {
+        let fixture = self
+            .fixtures
+            .commands
+            .iter()
+            .find(|f| f.matches(command))
+            .ok_or_else(|| anyhow::anyhow!("no fixture for command: {command}"))?;
+        let response = fixture.next();
+        tokio::time::sleep(Duration::from_millis(response.delay_ms)).await;
+        if let Some(err) = response.error {
+            return Err(transport_error(&err));
+        }
+        Ok(CommandOutput {
+            stdout: response.stdout,
+            stderr: response.stderr,
+            exit_status: response.exit_status,
+        })
+    }
+
+    /// 逐条回放，夹具无需关心批量脚本的格式；脚本化的传输错误使整批失败
+    async fn execute_batch(&self, commands: &[&str]) -> Result<Vec<Result<CommandOutput>>> {
+        let mut outputs = Vec::with_capacity(commands.len());
+        for command in commands {
+            match self.execute(command).await {
+                Err(e) if is_transport_error(&e) => return Err(e),
+                result => outputs.push(result),
+            }
+        }
+        Ok(outputs)
+    }
+}
@@ -6,7 +6,10 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 pub mod plain;
 pub mod tui;
 
-use crate::model::{MonitorEvent, MonitorKind, MonitorPayload};
+use crate::agent::Limiter;
+use crate::config::ServerConfig;
+use crate::model::{Alert, ErrorKind, MonitorEvent, MonitorKind, MonitorPayload};
+use chrono::{DateTime, Local, Utc};
 use crate::monitor::Monitorable;
 pub use plain::spawn_plain;
 pub use tui::spawn_tui;
@@ -19,23 +22,53 @@ pub enum DisplayKind {
 }
 
 
-struct AppState {
+/// 某台服务器最近一次未恢复的错误
+struct LastError {
+    kind: Option<MonitorKind>,
+    category: ErrorKind,
+    message: String,
+}
+
+/// 每台服务器保留的最近事件数
+const MAX_ALERTS: usize = 5;
+
+pub(crate) struct AppState {
     data: HashMap<String, HashMap<MonitorKind, MonitorPayload>>,
+    errors: HashMap<String, LastError>,
+    /// 每台服务器最近的事件，新的在后
+    alerts: HashMap<String, Vec<(DateTime<Utc>, MonitorKind, Alert)>>,
     servers: Vec<String>,
+    /// 可切换的页面，按键 1-9 依次对应
+    kinds: Vec<MonitorKind>,
+    /// 用于显示排队中与执行中的连接/命令数
+    limiter: Limiter,
     current_server: AtomicUsize,
     current_kind: AtomicUsize,
 }
 
 impl AppState {
-    fn new(servers: Vec<String>) -> Self {
+    pub(crate) fn new(servers: Vec<String>, kinds: Vec<MonitorKind>, limiter: Limiter) -> Self {
         Self {
             data: HashMap::new(),
+            errors: HashMap::new(),
+            alerts: HashMap::new(),
             servers,
+            kinds,
+            limiter,
             current_server: AtomicUsize::new(0),
             current_kind: AtomicUsize::new(0),
         }
     }
 
+    /// 当前页面上显示的采样
+    pub(crate) fn current_payload(&self) -> Option<&MonitorPayload> {
+        let server = self
+            .servers
+            .get(self.current_server.load(Ordering::Relaxed))?;
+        let kind = self.kinds.get(self.current_kind.load(Ordering::Relaxed))?;
+        self.data.get(server)?.get(kind)
+    }
+
     fn next_server(&mut self) {
         if !self.servers.is_empty() {
             self.current_server.store(
@@ -57,12 +90,21 @@ impl AppState {
 
     fn set_kind(&mut self, idx: usize) {
         self.current_kind.store(
-            idx.min(3), // 0-3 对应 4 种类型
+            idx.min(self.kinds.len().saturating_sub(1)),
             Ordering::Relaxed,
         )
     }
 
-    fn update_event(&mut self, ev: MonitorEvent) {
+    /// 在页面之间循环切换，`step` 为 1 或 -1
+    fn cycle_kind(&mut self, step: isize) {
+        if !self.kinds.is_empty() {
+            let len = self.kinds.len() as isize;
+            let current = self.current_kind.load(Ordering::Relaxed) as isize;
+            self.set_kind((current + step).rem_euclid(len) as usize);
+        }
+    }
+
+    pub(crate) fn update_event(&mut self, ev: MonitorEvent) {
         match ev {
             MonitorEvent::Sample {
                 server,
@@ -71,28 +113,58 @@ impl AppState {
                 ..
             } => {
                 if self.servers.contains(&server) {
-                    self.data.entry(server).or_default().insert(kind, payload);
+                    // 同一监控项或连接层面的错误在拿到新样本后视为已恢复
+                    if self
+                        .errors
+                        .get(&server)
+                        .is_some_and(|e| e.kind.is_none() || e.kind.as_ref() == Some(&kind))
+                    {
+                        self.errors.remove(&server);
+                    }
+                    self.data.entry(server).or_default().insert(kind, *payload);
+                }
+            }
+            MonitorEvent::Alert {
+                server,
+                kind,
+                alert,
+                timestamp,
+            } => {
+                let alerts = self.alerts.entry(server).or_default();
+                alerts.push((timestamp, kind, alert));
+                if alerts.len() > MAX_ALERTS {
+                    alerts.remove(0);
                 }
             }
             MonitorEvent::Error {
                 server,
                 kind,
+                category,
                 error,
                 ..
             } => {
-                eprintln!("[{}][{:?}]: {}", server, kind, error);
+                self.errors.insert(
+                    server,
+                    LastError {
+                        kind,
+                        category,
+                        message: error,
+                    },
+                );
             }
         }
     }
 
     /// 处理键盘事件，返回 true 表示请求退出
-    fn handle_key(&mut self, code: KeyCode) -> bool {
+    pub(crate) fn handle_key(&mut self, code: KeyCode) -> bool {
         match code {
             KeyCode::Char('n') => self.next_server(),
             KeyCode::Char('l') => self.prev_server(),
-            KeyCode::Char(c) if ('1'..='4').contains(&c) => {
+            KeyCode::Char(c) if ('1'..='9').contains(&c) => {
                 self.set_kind((c as u8 - b'1') as usize)
             }
+            KeyCode::Tab => self.cycle_kind(1),
+            KeyCode::BackTab => self.cycle_kind(-1),
             KeyCode::Esc | KeyCode::Char('q') => return true,
             _ => {}
         }
@@ -100,36 +172,89 @@ impl AppState {
     }
 }
 
+/// UI 的页面列表：固定的 MEM/CPU/DISK/NET，其后是各服务器配置的其它监控项
+pub fn page_kinds(servers: &[ServerConfig]) -> Vec<MonitorKind> {
+    let mut kinds = vec![
+        MonitorKind::Mem,
+        MonitorKind::Cpu,
+        MonitorKind::Disk,
+        MonitorKind::Net,
+    ];
+    for kind in servers.iter().flat_map(|s| &s.monitors) {
+        if !kinds.contains(kind) {
+            kinds.push(kind.clone());
+        }
+    }
+    kinds
+}
+
+/// 页面切换的按键说明，如 `[1-4] MEM/CPU/DISK/NET`
+pub(crate) fn pages_help(state: &AppState) -> String {
+    let names: Vec<String> = state.kinds.iter().map(MonitorKind::label).collect();
+    let mut help = format!("[1-{}] {}", state.kinds.len().min(9), names.join("/"));
+    if state.kinds.len() > 4 {
+        help.push_str("  [TAB] NEXT PAGE");
+    }
+    help
+}
 
 /// 生成主显示文本
-fn main_text(state: &AppState, kinds: &[MonitorKind; 4]) -> String {
+pub(crate) fn main_text(state: &AppState) -> String {
+    text_with(state, |payload| payload.common_display())
+}
+
+/// TUI 另用表格绘制 unit 时的主显示文本：Systemd 页只保留摘要行
+pub(crate) fn main_text_without_units(state: &AppState) -> String {
+    text_with(state, |payload| match payload {
+        MonitorPayload::Systemd(info) => info.summary(),
+        other => other.common_display(),
+    })
+}
+
+/// 由 `display` 生成当前页监控项的文本，其余部分（标题、事件、错误）各页相同
+fn text_with(state: &AppState, display: impl Fn(&MonitorPayload) -> String) -> String {
     if state.servers.is_empty() {
         "NO SERVERS DATA".to_string()
     } else {
         let server = &state.servers[state.current_server.load(Ordering::Relaxed)];
-        let kind = &kinds[state.current_kind.load(Ordering::Relaxed)];
-        let kind_name = match kind {
-            MonitorKind::Mem => "MEM",
-            MonitorKind::Cpu => "CPU",
-            MonitorKind::Disk => "DISK",
-            MonitorKind::Net => "NET",
-        };
+        let kind = &state.kinds[state.current_kind.load(Ordering::Relaxed)];
+        let kind_name = kind.label();
         let mut t = format!(
-            "=== Server: {} ({}/{}) ===\n[{}] ",
+            "=== Server: {} ({}/{}) === queue: {} waiting, {}/{} running\n[{}] ",
             server,
             state.current_server.load(Ordering::Relaxed) + 1,
             state.servers.len(),
+            state.limiter.queued(),
+            state.limiter.running(),
+            state.limiter.limit(),
             kind_name
         );
         if let Some(map) = state.data.get(server) {
             if let Some(payload) = map.get(kind) {
-                t.push_str(&payload.common_display());
+                t.push_str(&display(payload));
             } else {
                 t.push_str("NO DATA");
             }
         } else {
             t.push_str("NO DATA");
         }
+        for (timestamp, kind, alert) in state.alerts.get(server).into_iter().flatten() {
+            t.push_str(&format!(
+                "\n>> {} [{}] {}",
+                timestamp.with_timezone(&Local).format("%H:%M:%S"),
+                kind.label(),
+                alert
+            ));
+        }
+        if let Some(err) = state.errors.get(server) {
+            let label = match err.category {
+                ErrorKind::HostKeyMismatch => "HOST KEY MISMATCH",
+                ErrorKind::HostKeyUnknown => "HOST KEY VERIFICATION FAILED",
+                ErrorKind::Connect => "CONNECTION ERROR",
+                ErrorKind::Monitor => "ERROR",
+            };
+            t.push_str(&format!("\n!! {}: {}", label, err.message));
+        }
         t
     }
 }
\ No newline at end of file
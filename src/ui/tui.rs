@@ -1,10 +1,11 @@
 use crossterm::event::{Event, KeyEvent};
 use crossterm::{cursor, event, execute, terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType}};
 use ratatui::{
-    backend::CrosstermBackend,
+    backend::{Backend, CrosstermBackend},
     layout::{Constraint, Direction, Layout},
     style::{Color, Style},
-    widgets::{Block, Borders, Paragraph},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
     Terminal,
 };
 use std::io::stdout;
@@ -12,8 +13,11 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{broadcast, mpsc, Mutex, RwLock};
 
-use crate::model::{MonitorEvent, MonitorKind};
-use crate::ui::{main_text, AppState};
+use crate::agent::Limiter;
+use crate::model::{MonitorEvent, MonitorKind, MonitorPayload};
+use crate::monitor::systemd::{SystemdInfo, UNIT_HEADERS};
+use crate::monitor::CpuInfo;
+use crate::ui::{main_text, main_text_without_units, pages_help, AppState};
 
 /// spawn_tui 返回一个 JoinHandle，包含主循环 + 渲染任务
 pub fn spawn_tui(
@@ -21,17 +25,13 @@ pub fn spawn_tui(
     interval_ms: u64,
     shutdown_tx: broadcast::Sender<()>,
     servers: Vec<String>,
+    kinds: Vec<MonitorKind>,
+    limiter: Limiter,
 ) -> tokio::task::JoinHandle<()> {
     tokio::spawn(async move {
         let mut shutdown_rx = shutdown_tx.subscribe();
         // 共享状态
-        let state = Arc::new(RwLock::new(AppState::new(servers)));
-        let kinds = [
-            MonitorKind::Mem,
-            MonitorKind::Cpu,
-            MonitorKind::Disk,
-            MonitorKind::Net,
-        ];
+        let state = Arc::new(RwLock::new(AppState::new(servers, kinds, limiter)));
 
         // 初始化终端
         let terminal = Arc::new(Mutex::new(
@@ -50,21 +50,20 @@ pub fn spawn_tui(
         let render_handle = tokio::spawn(async move {
             loop {
                 // 处理键盘事件（非阻塞）
-                if event::poll(Duration::from_millis(50)).unwrap_or(false) {
-                    if let Event::Key(KeyEvent { code, kind, .. }) = event::read().unwrap() {
-                        if kind == event::KeyEventKind::Press {
-                            let mut state = value.write().await;
-                            if state.handle_key(code) {
-                                let _ = shutdown_tx.send(());
-                                break;
-                            }
-                        }
+                if event::poll(Duration::from_millis(50)).unwrap_or(false)
+                    && let Event::Key(KeyEvent { code, kind, .. }) = event::read().unwrap()
+                    && kind == event::KeyEventKind::Press
+                {
+                    let mut state = value.write().await;
+                    if state.handle_key(code) {
+                        let _ = shutdown_tx.send(());
+                        break;
                     }
                 }
                 {
                     let state = render_state.read().await;
                     let mut term = render_term.lock().await;
-                    render(&mut term, &state, &kinds);
+                    render(&mut term, &state);
                 }
             }
         });
@@ -99,19 +98,46 @@ pub fn spawn_tui(
 
 
 /// 渲染函数，只读取状态，不修改
-fn render(
-    term: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+pub(crate) fn render<B: Backend>(
+    term: &mut Terminal<B>,
     state: &AppState,
-    kinds: &[MonitorKind; 4],
 ) {
-    let text = main_text(state, kinds);
-    let help = "[N/L] NEXT/LAST SERVER  [1-4] MEM/CPU/DISK/NET  [Q] QUIT";
+    let help = format!("[N/L] NEXT/LAST SERVER  {}  [Q] QUIT", pages_help(state));
+    let cores = match state.current_payload() {
+        Some(MonitorPayload::Cpu(info)) if !info.core_usage.is_empty() => Some(info),
+        _ => None,
+    };
+    let units = match state.current_payload() {
+        Some(MonitorPayload::Systemd(info)) if !info.units.is_empty() => Some(info),
+        _ => None,
+    };
+    // unit 由下方的表格显示，文本中不再重复
+    let text = if units.is_some() {
+        main_text_without_units(state)
+    } else {
+        main_text(state)
+    };
 
     let _ = term.draw(|f| {
+        // 每个核占 3 列，加上边框所需的行数
+        let strip_height = cores.map_or(0, |info| {
+            let width = f.area().width.saturating_sub(4).max(3) as usize;
+            (info.core_usage.len() * 3).div_ceil(width) as u16 + 2
+        });
+        // 每个 unit 一行，加上表头与边框；高度不超过外边距、帮助行以及
+        // 文本的标题行、摘要行与边框之外的剩余空间
+        let available = f.area().height.saturating_sub(2 + 2 + 4);
+        let strip_height = units.map_or(strip_height, |info| {
+            (info.units.len() as u16 + 3).min(available)
+        });
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .margin(1)
-            .constraints([Constraint::Min(3), Constraint::Length(2)])
+            .constraints([
+                Constraint::Min(3),
+                Constraint::Length(strip_height),
+                Constraint::Length(2),
+            ])
             .split(f.area());
         f.render_widget(
             Paragraph::new(text)
@@ -123,9 +149,61 @@ fn render(
                 .style(Style::default().fg(Color::White)),
             chunks[0],
         );
+        if let Some(info) = cores {
+            f.render_widget(
+                Paragraph::new(heat_strip(info))
+                    .wrap(Wrap { trim: false })
+                    .block(Block::default().borders(Borders::ALL).title("Per-core usage")),
+                chunks[1],
+            );
+        }
+        if let Some(info) = units {
+            f.render_widget(unit_table(info), chunks[1]);
+        }
         f.render_widget(
             Paragraph::new(help).style(Style::default().fg(Color::Yellow)),
-            chunks[1],
+            chunks[2],
         );
     });
 }
+
+/// 每个核一个色块，颜色随使用率由绿变红
+fn heat_strip(info: &CpuInfo) -> Line<'static> {
+    let spans = info.core_usage.iter().map(|usage| {
+        let color = match *usage {
+            u if u >= 90.0 => Color::Red,
+            u if u >= 70.0 => Color::LightRed,
+            u if u >= 40.0 => Color::Yellow,
+            u if u >= 10.0 => Color::Green,
+            _ => Color::DarkGray,
+        };
+        Span::styled("██ ", Style::default().fg(color))
+    });
+    Line::from(spans.collect::<Vec<_>>())
+}
+
+/// active 为绿色，failed 为红色，其余状态（启动中、未运行等）为黄色
+fn unit_table(info: &SystemdInfo) -> Table<'static> {
+    let rows = info.units.iter().map(|unit| {
+        let color = if unit.is_active() {
+            Color::Green
+        } else if unit.is_failed() || unit.load_state == "not-found" {
+            Color::Red
+        } else {
+            Color::Yellow
+        };
+        Row::new(unit.columns().map(Cell::from)).style(Style::default().fg(color))
+    });
+    let widths = [
+        Constraint::Min(20),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(8),
+        Constraint::Length(10),
+        Constraint::Length(7),
+        Constraint::Length(12),
+    ];
+    Table::new(rows, widths)
+        .header(Row::new(UNIT_HEADERS).style(Style::default().fg(Color::White)))
+        .block(Block::default().borders(Borders::ALL).title("Units"))
+}